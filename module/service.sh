@@ -20,6 +20,7 @@ MODDIR=${0%/*}
 DIR=/sdcard/Android/fas-rs
 MERGE_FLAG=$DIR/.need_merge
 LOG=$DIR/fas_log.txt
+NODE_PATH_RECORD=$DIR/node_path
 
 sh $MODDIR/vtools/init_vtools.sh $(realpath $MODDIR/module.prop)
 
@@ -36,4 +37,13 @@ if [ -f $MERGE_FLAG ]; then
 fi
 
 killall fas-rs
+rm -f $NODE_PATH_RECORD
 RUST_BACKTRACE=1 nohup $MODDIR/fas-rs run $MODDIR/games.toml >$LOG 2>&1 &
+
+# the node directory is only known once fas-rs picked one
+{
+	until [ -f $NODE_PATH_RECORD ]; do
+		sleep 1
+	done
+	sh $MODDIR/vtools/init_vtools.sh $(realpath $MODDIR/module.prop)
+} &
@@ -17,6 +17,10 @@
 # with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
 DIR=/sdcard/Android/fas-rs
+NODE_DIR=/data/adb/fas-rs
+
+umount $NODE_DIR/tmpfs 2>/dev/null
+rm -rf $NODE_DIR
 
 {
 	until [ -d $DIR ] && [ -d /data ]; do
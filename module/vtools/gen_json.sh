@@ -19,6 +19,13 @@
 propPath=$1
 version=$(cat $propPath | grep "version=" | cut -d "=" -f2)
 versionCode=$(cat $propPath | grep "versionCode=" | cut -d "=" -f2)
+NODE_PATH_RECORD=/sdcard/Android/fas-rs/node_path
+
+if [ -f $NODE_PATH_RECORD ]; then
+	mode=$(cat $NODE_PATH_RECORD)/mode
+else
+	mode=/dev/fas_rs/mode
+fi
 
 json=$(
 	cat <<EOF
@@ -32,7 +39,7 @@ json=$(
         "pedestal": true
     },
     "module": "fas_rs",
-    "state": "/dev/fas_rs/mode",
+    "state": "$mode",
     "entry": "/data/powercfg.sh",
     "projectUrl": "https://github.com/shadow3aaa/fas-rs"
 }
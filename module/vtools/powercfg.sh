@@ -16,7 +16,13 @@
 # You should have received a copy of the GNU General Public License along
 # with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
-mode=/dev/fas_rs/mode
+NODE_PATH_RECORD=/sdcard/Android/fas-rs/node_path
+
+if [ -f $NODE_PATH_RECORD ]; then
+	mode=$(cat $NODE_PATH_RECORD)/mode
+else
+	mode=/dev/fas_rs/mode
+fi
 
 case "$1" in
 "init" | "fast" | "pedestal") echo fast >$mode ;;
@@ -17,21 +17,47 @@
 
 use std::{
     fs,
+    io::ErrorKind,
+    iter,
     path::{Path, PathBuf},
+    str::FromStr,
     sync::atomic::Ordering,
+    time::{Duration, Instant},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use log::{error, info, warn};
 
 use super::IGNORE_MAP;
 use crate::file_handler::FileHandler;
 
+const UNHEALTHY_AFTER_FAILURES: u32 = 5;
+const WRITE_BACKOFF: Duration = Duration::from_millis(100);
+const WRITE_BACKOFF_MAX: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+struct WriteFailure {
+    failures: u32,
+    retry_at: Instant,
+    error: String,
+    unhealthy: bool,
+}
+
 #[derive(Debug)]
 pub struct Info {
     pub policy: i32,
     path: PathBuf,
     pub cur_fas_freq: isize,
     pub freqs: Vec<isize>,
+    boost_freqs: Vec<isize>,
+    max_normal_freq: isize,
+    // Includes the skipped opps
+    hw_min_freq: isize,
+    cpus: Vec<usize>,
+    group_paths: Vec<PathBuf>,
+    rewrite_interval: Duration,
+    last_write: Option<Instant>,
+    write_failure: Option<WriteFailure>,
 }
 
 impl Info {
@@ -46,29 +72,147 @@ impl Info {
             .parse::<i32>()
             .context("Failed to parse policy")?;
 
-        let freqs_content = fs::read_to_string(path.join("scaling_available_frequencies"))
+        let mut freqs: Vec<isize> = read_values(&path.join("scaling_available_frequencies"))
             .context("Failed to read frequencies")?;
-        let mut freqs: Vec<isize> = freqs_content
-            .split_whitespace()
-            .map(|f| f.parse::<isize>().context("Failed to parse frequency"))
-            .collect::<Result<_>>()?;
+
+        // Boost frequencies, only selected when allowed
+        let boost_freqs: Vec<isize> =
+            read_values(&path.join("scaling_boost_frequencies")).unwrap_or_default();
+        freqs.extend(&boost_freqs);
         freqs.sort_unstable();
+        freqs.dedup();
+
+        let max_normal_freq = freqs
+            .iter()
+            .copied()
+            .filter(|freq| !boost_freqs.contains(freq))
+            .max()
+            .or_else(|| freqs.last().copied())
+            .context("No frequencies available")?;
+
+        let hw_min_freq = *freqs.first().context("No frequencies available")?;
+        let cpus = read_values(&path.join("affected_cpus")).unwrap_or_default();
 
         Ok(Self {
             policy,
             path,
-            cur_fas_freq: *freqs.last().context("No frequencies available")?,
+            cur_fas_freq: max_normal_freq,
             freqs,
+            boost_freqs,
+            max_normal_freq,
+            hw_min_freq,
+            cpus,
+            group_paths: Vec::new(),
+            rewrite_interval: Duration::ZERO,
+            last_write: None,
+            write_failure: None,
         })
     }
 
-    pub fn write_freq(&mut self, freq: isize, file_handler: &mut FileHandler) -> Result<()> {
+    // Keeps at least two entries
+    pub fn skip_lowest_freqs(&mut self, count: usize) {
+        let count = count.min(self.freqs.len().saturating_sub(2));
+        if count == 0 {
+            return;
+        }
+
+        let skipped: Vec<_> = self.freqs.drain(..count).collect();
+        info!("policy{}: excluded frequencies {:?}", self.policy, skipped);
+    }
+
+    pub const fn set_rewrite_interval(&mut self, interval: Duration) {
+        self.rewrite_interval = interval;
+    }
+
+    // Single-core policies with the same table and adjacent cpus
+    pub fn can_group_with(&self, other: &Self) -> bool {
+        self.cpus.len() == self.group_paths.len() + 1
+            && other.cpus.len() == 1
+            && self.freqs == other.freqs
+            && self
+                .cpus
+                .last()
+                .is_some_and(|cpu| other.cpus.first() == Some(&(cpu + 1)))
+    }
+
+    pub fn group_with(&mut self, other: Self) {
+        self.cpus.extend(other.cpus);
+        self.group_paths.push(other.path);
+    }
+
+    pub fn cpus(&self) -> &[usize] {
+        &self.cpus
+    }
+
+    // State N maps to the N-th highest frequency
+    pub fn throttle_ceiling(&self, state: u64) -> Option<isize> {
+        let state = usize::try_from(state).ok()?;
+        if state == 0 {
+            return None;
+        }
+
+        let normal_freqs: Vec<_> = self
+            .freqs
+            .iter()
+            .copied()
+            .filter(|freq| !self.boost_freqs.contains(freq))
+            .collect();
+        let index = normal_freqs.len().checked_sub(1)?.saturating_sub(state);
+        normal_freqs.get(index).copied()
+    }
+
+    // Only once the real frequency confirms it
+    pub fn confirmed_throttle_ceiling(&self, state: u64) -> Option<isize> {
+        let ceiling = self.throttle_ceiling(state)?;
+        self.read_freq()
+            .is_ok_and(|freq| freq <= ceiling)
+            .then_some(ceiling)
+    }
+
+    // With boost allowed, max_per is relative to the real table top
+    pub fn clamp_freq_per(
+        &self,
+        freq: isize,
+        min_per: u8,
+        max_per: u8,
+        allow_boost: bool,
+    ) -> isize {
+        let top = if allow_boost {
+            self.freqs.last().copied().unwrap_or(self.max_normal_freq)
+        } else {
+            self.max_normal_freq
+        };
+        let max_per = max_per.min(100);
+        let min_per = min_per.min(max_per);
+        let min_freq = self.max_normal_freq * isize::from(min_per) / 100;
+        let max_freq = top * isize::from(max_per) / 100;
+        freq.clamp(min_freq, max_freq)
+    }
+
+    pub fn write_freq(
+        &mut self,
+        freq: isize,
+        allow_boost: bool,
+        file_handler: &mut FileHandler,
+    ) -> Result<()> {
         let min_freq = *self.freqs.first().context("No frequencies available")?;
-        let max_freq = *self.freqs.last().context("No frequencies available")?;
+        let max_freq = if allow_boost {
+            *self.freqs.last().context("No frequencies available")?
+        } else {
+            self.max_normal_freq
+        };
 
         let adjusted_freq = freq.clamp(min_freq, max_freq);
+
+        // Rewrite unchanged frequencies now and then
+        if adjusted_freq == self.cur_fas_freq
+            && self
+                .last_write
+                .is_some_and(|last_write| last_write.elapsed() < self.rewrite_interval)
+        {
+            return Ok(());
+        }
         self.cur_fas_freq = adjusted_freq;
-        let adjusted_freq = adjusted_freq.to_string();
 
         if !IGNORE_MAP
             .get()
@@ -77,44 +221,571 @@ impl Info {
             .context("Policy ignore flag not found")?
             .load(Ordering::Acquire)
         {
-            file_handler.write_with_workround(self.max_freq_path(), &adjusted_freq)?;
-            file_handler.write_with_workround(self.min_freq_path(), &adjusted_freq)?;
+            if self
+                .write_failure
+                .as_ref()
+                .is_some_and(|failure| Instant::now() < failure.retry_at)
+            {
+                return Ok(());
+            }
+
+            let result = self.paths().try_for_each(|path| {
+                let written_freq = self.write_max_freq(path, adjusted_freq, file_handler)?;
+                file_handler.write_with_workround(min_freq_path(path), written_freq.to_string())
+            });
+            self.last_write = result.is_ok().then(Instant::now);
+            if result.is_err() && self.retry_pending(file_handler) {
+                return Ok(());
+            }
+            self.record_write(&result);
+            result?;
         }
         Ok(())
     }
 
-    pub fn reset_freq(&self, file_handler: &mut FileHandler) -> Result<()> {
-        let min_freq = self
-            .freqs
-            .first()
-            .context("No frequencies available")?
-            .to_string();
-        let max_freq = self
-            .freqs
-            .last()
-            .context("No frequencies available")?
-            .to_string();
+    fn retry_pending(&self, file_handler: &FileHandler) -> bool {
+        self.paths().any(|path| {
+            file_handler.retry_pending(max_freq_path(path))
+                || file_handler.retry_pending(min_freq_path(path))
+        })
+    }
 
-        file_handler.write_with_workround(self.max_freq_path(), &max_freq)?;
-        file_handler.write_with_workround(self.min_freq_path(), &min_freq)?;
+    fn record_write(&mut self, result: &Result<()>) {
+        match (result, &mut self.write_failure) {
+            (Ok(()), None) => (),
+            (Ok(()), Some(_)) => {
+                info!("policy{}: frequency writes recovered", self.policy);
+                self.write_failure = None;
+            }
+            (Err(e), None) => {
+                warn!("policy{}: failed to write frequency: {e}", self.policy);
+                self.write_failure = Some(WriteFailure {
+                    failures: 1,
+                    retry_at: Instant::now() + WRITE_BACKOFF,
+                    error: e.to_string(),
+                    unhealthy: false,
+                });
+            }
+            (Err(e), Some(failure)) => {
+                failure.failures += 1;
+                failure.retry_at = Instant::now() + write_backoff(failure.failures);
+                failure.error = e.to_string();
+                if !failure.unhealthy && failure.failures >= UNHEALTHY_AFTER_FAILURES {
+                    failure.unhealthy = true;
+                    error!(
+                        "policy{}: {} frequency writes failed in a row, marking unhealthy: {e}",
+                        self.policy, failure.failures
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn write_error(&self) -> Option<&str> {
+        self.write_failure
+            .as_ref()
+            .filter(|failure| failure.unhealthy)
+            .map(|failure| failure.error.as_str())
+    }
+
+    // Moves at most `step` table entries per call
+    pub fn write_freq_ramped(
+        &mut self,
+        freq: isize,
+        allow_boost: bool,
+        ramp_step: usize,
+        file_handler: &mut FileHandler,
+    ) -> Result<()> {
+        if ramp_step > 0 {
+            let cur_pos = self.freqs.partition_point(|&f| f < self.cur_fas_freq);
+            let target_pos = self.freqs.partition_point(|&f| f < freq);
+
+            if cur_pos.abs_diff(target_pos) > ramp_step {
+                let pos = if cur_pos < target_pos {
+                    cur_pos + ramp_step
+                } else {
+                    cur_pos - ramp_step
+                };
+                return self.write_freq(self.freqs[pos], allow_boost, file_handler);
+            }
+        }
+
+        self.write_freq(freq, allow_boost, file_handler)
+    }
+
+    // Falls back to the highest normal frequency on EINVAL
+    fn write_max_freq(
+        &self,
+        path: &Path,
+        freq: isize,
+        file_handler: &mut FileHandler,
+    ) -> Result<isize> {
+        if self.boost_freqs.contains(&freq) {
+            match file_handler.write(max_freq_path(path), freq.to_string()) {
+                Ok(()) => return Ok(freq),
+                Err(e) if e.kind() == ErrorKind::InvalidInput => {
+                    file_handler.write_with_workround(
+                        max_freq_path(path),
+                        self.max_normal_freq.to_string(),
+                    )?;
+                    return Ok(self.max_normal_freq);
+                }
+                Err(_) => (),
+            }
+        }
+
+        file_handler.write_with_workround(max_freq_path(path), freq.to_string())?;
+        Ok(freq)
+    }
+
+    pub fn reset_freq(&mut self, file_handler: &mut FileHandler) -> Result<()> {
+        self.last_write = None;
+
+        let min_freq = self.hw_min_freq.to_string();
+        // Restore the real hardware top
+        let max_freq = *self.freqs.last().context("No frequencies available")?;
+
+        for path in self.paths() {
+            self.write_max_freq(path, max_freq, file_handler)?;
+            file_handler.write_with_workround(min_freq_path(path), &min_freq)?;
+        }
         Ok(())
     }
 
-    pub fn read_freq(&self) -> isize {
-        fs::read_to_string(self.path.join("scaling_cur_freq"))
-            .context("Failed to read scaling_cur_freq")
-            .unwrap()
-            .trim()
-            .parse::<isize>()
+    pub fn read_freq(&self) -> Result<isize> {
+        read_values(&self.path.join("scaling_cur_freq"))
+            .context("Failed to read scaling_cur_freq")?
+            .first()
+            .copied()
             .context("Failed to parse scaling_cur_freq")
-            .unwrap()
     }
 
-    fn max_freq_path(&self) -> PathBuf {
-        self.path.join("scaling_max_freq")
+    fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        iter::once(&self.path).chain(&self.group_paths)
+    }
+}
+
+fn write_backoff(failures: u32) -> Duration {
+    WRITE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(WRITE_BACKOFF_MAX)
+}
+
+fn max_freq_path(path: &Path) -> PathBuf {
+    path.join("scaling_max_freq")
+}
+
+fn min_freq_path(path: &Path) -> PathBuf {
+    path.join("scaling_min_freq")
+}
+
+// Keeps the numeric prefix, some kernels leave stray bytes behind
+fn read_values<T: FromStr>(path: &Path) -> Result<Vec<T>> {
+    let content = fs::read_to_string(path)?;
+    let mut values = Vec::new();
+
+    for token in content.split(|c: char| c.is_whitespace() || c == '\0') {
+        let end = token
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(token.len());
+        let (value, garbage) = token.split_at(end);
+
+        if !garbage.is_empty() {
+            warn!("Discarded {garbage:?} while parsing {path:?}");
+        }
+
+        if let Ok(value) = value.parse() {
+            values.push(value);
+        }
+    }
+
+    if values.is_empty() {
+        bail!("No valid value in {path:?}");
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::PathBuf, sync::atomic::AtomicBool, time::Instant};
+
+    use super::{
+        IGNORE_MAP, Info, UNHEALTHY_AFTER_FAILURES, WRITE_BACKOFF, WRITE_BACKOFF_MAX, read_values,
+        write_backoff,
+    };
+    use crate::{
+        file_handler::FileHandler,
+        test_utils::{fixture_dir, write_files},
+    };
+
+    fn policy_fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let path = fixture_dir(&format!("cpu-info/{name}/policy0"));
+        write_files(&path, files);
+        path
+    }
+
+    // Fixtures are all policy0, and write_freq needs its ignore flag
+    fn init_ignore_map() {
+        IGNORE_MAP.get_or_init(|| HashMap::from([(0, AtomicBool::new(false))]));
+    }
+
+    fn table_fixture(name: &str, freqs: &str) -> Info {
+        let path = policy_fixture(
+            name,
+            &[
+                ("scaling_available_frequencies", freqs),
+                ("scaling_max_freq", ""),
+                ("scaling_min_freq", ""),
+            ],
+        );
+        Info::new(path).unwrap()
+    }
+
+    #[test]
+    fn table_is_sorted_and_deduplicated() {
+        let info = table_fixture("sort-dedup", "1000000 300000 2000000 1000000 300000\n");
+
+        assert_eq!(info.freqs, [300_000, 1_000_000, 2_000_000]);
+        assert_eq!(info.max_normal_freq, 2_000_000);
+    }
+
+    #[test]
+    fn descending_table_is_sorted() {
+        // Exynos kernels list the table from the top
+        let freqs = "2400000 2000000 1500000 1000000 300000 \n";
+        let path = policy_fixture(
+            "descending",
+            &[
+                ("scaling_available_frequencies", freqs),
+                ("scaling_boost_frequencies", "2400000\n"),
+            ],
+        );
+        let raw: Vec<isize> = read_values(&path.join("scaling_available_frequencies")).unwrap();
+        let info = Info::new(&path).unwrap();
+
+        assert_eq!(raw.first(), Some(&2_400_000));
+        assert!(info.freqs.is_sorted());
+        assert_eq!(info.freqs.last(), Some(&2_400_000));
+        assert_eq!(info.max_normal_freq, 2_000_000);
+        assert_eq!(info.cur_fas_freq, 2_000_000);
+        assert_eq!(info.throttle_ceiling(1), Some(1_500_000));
+    }
+
+    #[test]
+    fn clamp_follows_percentages() {
+        let info = table_fixture("clamp-per", "300000 1000000 1500000 2000000\n");
+
+        assert_eq!(info.clamp_freq_per(2_000_000, 0, 100, false), 2_000_000);
+        assert_eq!(info.clamp_freq_per(2_000_000, 0, 50, false), 1_000_000);
+        assert_eq!(info.clamp_freq_per(2_000_000, 0, 0, false), 0);
+        assert_eq!(info.clamp_freq_per(300_000, 50, 100, false), 1_000_000);
+        assert_eq!(info.clamp_freq_per(1_200_000, 0, 100, false), 1_200_000);
+        // Out of range percentages are clamped instead of asserted
+        assert_eq!(info.clamp_freq_per(2_000_000, 0, 150, false), 2_000_000);
+        assert_eq!(info.clamp_freq_per(300_000, 80, 50, false), 1_000_000);
+    }
+
+    #[test]
+    fn clamped_freq_lands_on_the_table() {
+        init_ignore_map();
+        let mut file_handler = FileHandler::new();
+
+        // 0% still writes the lowest entry, single and two entry tables have nothing in between
+        for (name, freqs, max_per, expected) in [
+            ("clamp-zero", "300000 1000000 2000000", 0, 300_000),
+            ("clamp-single", "1000000", 50, 1_000_000),
+            ("clamp-two", "300000 2000000", 10, 300_000),
+            ("clamp-two-top", "300000 2000000", 100, 2_000_000),
+        ] {
+            let mut info = table_fixture(name, freqs);
+            let freq = info.clamp_freq_per(2_000_000, 0, max_per, false);
+            info.write_freq(freq, false, &mut file_handler).unwrap();
+
+            assert_eq!(info.cur_fas_freq, expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn boost_freqs_are_merged_and_tagged() {
+        let path = policy_fixture(
+            "boost-split",
+            &[
+                ("scaling_available_frequencies", "300000 1000000 2000000\n"),
+                ("scaling_boost_frequencies", "2400000\n"),
+            ],
+        );
+        let info = Info::new(&path).unwrap();
+
+        assert_eq!(info.freqs, [300_000, 1_000_000, 2_000_000, 2_400_000]);
+        assert_eq!(info.boost_freqs, [2_400_000]);
+        assert_eq!(info.max_normal_freq, 2_000_000);
+        assert_eq!(info.cur_fas_freq, 2_000_000);
     }
 
-    fn min_freq_path(&self) -> PathBuf {
-        self.path.join("scaling_min_freq")
+    #[test]
+    fn missing_boost_file_means_no_boost_freqs() {
+        let path = policy_fixture(
+            "no-boost",
+            &[("scaling_available_frequencies", "300000 1000000 2000000\n")],
+        );
+        let info = Info::new(&path).unwrap();
+
+        assert!(info.boost_freqs.is_empty());
+        assert_eq!(info.max_normal_freq, 2_000_000);
+    }
+
+    #[test]
+    fn boost_only_table_falls_back_to_top() {
+        let path = policy_fixture(
+            "boost-only",
+            &[
+                ("scaling_available_frequencies", "2000000\n"),
+                ("scaling_boost_frequencies", "2000000 2400000\n"),
+            ],
+        );
+        let info = Info::new(&path).unwrap();
+
+        assert_eq!(info.freqs, [2_000_000, 2_400_000]);
+        assert_eq!(info.max_normal_freq, 2_400_000);
+    }
+
+    #[test]
+    fn clamp_reaches_boost_only_when_allowed() {
+        let path = policy_fixture(
+            "clamp-boost",
+            &[
+                ("scaling_available_frequencies", "300000 1000000 2000000\n"),
+                ("scaling_boost_frequencies", "2400000\n"),
+            ],
+        );
+        let info = Info::new(&path).unwrap();
+
+        assert_eq!(info.clamp_freq_per(2_400_000, 0, 100, true), 2_400_000);
+        assert_eq!(info.clamp_freq_per(2_400_000, 0, 100, false), 2_000_000);
+        assert_eq!(info.clamp_freq_per(2_400_000, 0, 50, true), 1_200_000);
+        assert_eq!(info.clamp_freq_per(0, 50, 100, true), 1_000_000);
+    }
+
+    #[test]
+    fn ramp_moves_one_step_per_call() {
+        init_ignore_map();
+        let path = policy_fixture(
+            "ramp",
+            &[
+                (
+                    "scaling_available_frequencies",
+                    "100000 200000 300000 400000 500000 600000 700000 800000\n",
+                ),
+                ("scaling_max_freq", "800000"),
+                ("scaling_min_freq", "100000"),
+            ],
+        );
+        let mut info = Info::new(&path).unwrap();
+        let mut file_handler = FileHandler::new();
+        let mut written = Vec::new();
+
+        for _ in 0..5 {
+            info.write_freq_ramped(100_000, false, 2, &mut file_handler)
+                .unwrap();
+            written.push(info.cur_fas_freq);
+        }
+
+        assert_eq!(written, [600_000, 400_000, 200_000, 100_000, 100_000]);
+    }
+
+    #[test]
+    fn skipped_opps_are_restored_on_reset() {
+        let path = policy_fixture(
+            "skip-reset",
+            &[
+                (
+                    "scaling_available_frequencies",
+                    "100000 200000 300000 400000 500000\n",
+                ),
+                ("scaling_max_freq", ""),
+                ("scaling_min_freq", ""),
+            ],
+        );
+        let mut info = Info::new(&path).unwrap();
+        info.skip_lowest_freqs(2);
+        assert_eq!(info.freqs, [300_000, 400_000, 500_000]);
+
+        info.skip_lowest_freqs(5);
+        assert_eq!(info.freqs, [400_000, 500_000]);
+
+        info.reset_freq(&mut FileHandler::new()).unwrap();
+        assert_eq!(
+            fs::read_to_string(path.join("scaling_min_freq")).unwrap(),
+            "100000"
+        );
+    }
+
+    #[test]
+    fn reset_restores_hardware_top() {
+        let path = policy_fixture(
+            "reset-top",
+            &[
+                ("scaling_available_frequencies", "300000 1000000 2000000\n"),
+                ("scaling_boost_frequencies", "2400000\n"),
+                ("scaling_max_freq", "1000000"),
+                ("scaling_min_freq", "1000000"),
+            ],
+        );
+        let mut info = Info::new(&path).unwrap();
+        info.reset_freq(&mut FileHandler::new()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(path.join("scaling_max_freq")).unwrap(),
+            "2400000"
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("scaling_min_freq")).unwrap(),
+            "300000"
+        );
+    }
+
+    fn read_fixture(name: &str, content: &str) -> anyhow::Result<Vec<usize>> {
+        let path = policy_fixture(name, &[("values", content)]);
+        read_values(&path.join("values"))
+    }
+
+    #[test]
+    fn read_values_ignores_trailing_nul() {
+        // Seen on a Samsung build
+        let values = read_fixture("nul", "300000 1000000 2000000 \0\0\n").unwrap();
+        assert_eq!(values, [300_000, 1_000_000, 2_000_000]);
+    }
+
+    #[test]
+    fn read_values_keeps_numeric_prefix() {
+        let values = read_fixture("stray", "0  1\t2 3x\n\n").unwrap();
+        assert_eq!(values, [0, 1, 2, 3]);
+
+        let values = read_fixture("stray-token", "4 cpu 5\0garbage 6\r\n").unwrap();
+        assert_eq!(values, [4, 5, 6]);
+    }
+
+    #[test]
+    fn read_values_rejects_empty_file() {
+        assert!(read_fixture("empty", "").is_err());
+        assert!(read_fixture("blank", " \n\0").is_err());
+    }
+
+    #[test]
+    fn read_values_rejects_all_garbage() {
+        assert!(read_fixture("garbage", "n/a\n").is_err());
+        assert!(read_fixture("garbage-tokens", "abc -1 x2\n").is_err());
+    }
+
+    #[test]
+    fn read_values_fails_on_missing_file() {
+        let path = policy_fixture("missing", &[]);
+        assert!(read_values::<usize>(&path.join("values")).is_err());
+    }
+
+    #[test]
+    fn throttle_states_map_to_normal_table_entries() {
+        let path = policy_fixture(
+            "throttle-map",
+            &[
+                (
+                    "scaling_available_frequencies",
+                    "300000 1000000 1500000 2000000 2400000\n",
+                ),
+                ("scaling_boost_frequencies", "2400000\n"),
+            ],
+        );
+        let info = Info::new(path).unwrap();
+
+        assert_eq!(info.throttle_ceiling(0), None);
+        // Boost frequencies are not cooling states
+        assert_eq!(info.throttle_ceiling(1), Some(1_500_000));
+        assert_eq!(info.throttle_ceiling(2), Some(1_000_000));
+        assert_eq!(info.throttle_ceiling(3), Some(300_000));
+        assert_eq!(info.throttle_ceiling(50), Some(300_000));
+        assert_eq!(info.throttle_ceiling(u64::MAX), Some(300_000));
+    }
+
+    #[test]
+    fn throttle_episode_is_followed_and_released() {
+        let freqs = "300000 1000000 1500000 2000000\n";
+        let path = policy_fixture(
+            "throttle-episode",
+            &[
+                ("scaling_available_frequencies", freqs),
+                ("scaling_cur_freq", "2000000\n"),
+            ],
+        );
+        let info = Info::new(&path).unwrap();
+
+        // (cooling state, scaling_cur_freq, expected ceiling)
+        let episode = [
+            (0, 2_000_000, None),
+            // The kernel has not applied the limit yet
+            (1, 2_000_000, None),
+            (1, 1_500_000, Some(1_500_000)),
+            (2, 1_000_000, Some(1_000_000)),
+            (1, 1_000_000, Some(1_500_000)),
+            (0, 1_000_000, None),
+        ];
+        for (state, cur_freq, expected) in episode {
+            fs::write(path.join("scaling_cur_freq"), format!("{cur_freq}\n")).unwrap();
+            assert_eq!(
+                info.confirmed_throttle_ceiling(state),
+                expected,
+                "state {state}"
+            );
+        }
+
+        fs::remove_file(path.join("scaling_cur_freq")).unwrap();
+        assert_eq!(info.confirmed_throttle_ceiling(1), None);
+    }
+
+    #[test]
+    fn write_backoff_doubles_up_to_the_cap() {
+        let backoffs: Vec<_> = (1..=8).map(write_backoff).collect();
+
+        assert_eq!(backoffs[0], WRITE_BACKOFF);
+        assert_eq!(backoffs[1], WRITE_BACKOFF * 2);
+        assert_eq!(backoffs[2], WRITE_BACKOFF * 4);
+        assert_eq!(backoffs[7], WRITE_BACKOFF_MAX);
+        assert_eq!(write_backoff(u32::MAX), WRITE_BACKOFF_MAX);
+    }
+
+    #[test]
+    fn consecutive_failures_mark_the_policy_unhealthy() {
+        init_ignore_map();
+        let path = policy_fixture(
+            "unhealthy",
+            &[
+                ("scaling_available_frequencies", "300000 1000000\n"),
+                ("scaling_min_freq", ""),
+            ],
+        );
+        // A directory can't be opened for writing
+        fs::create_dir(path.join("scaling_max_freq")).unwrap();
+        let mut info = Info::new(&path).unwrap();
+        let mut file_handler = FileHandler::new();
+
+        for failures in 1..UNHEALTHY_AFTER_FAILURES {
+            assert!(info.write_freq(300_000, false, &mut file_handler).is_err());
+            assert_eq!(info.write_failure.as_ref().unwrap().failures, failures);
+            assert!(info.write_error().is_none());
+
+            // Backing off, the node is left alone
+            assert!(info.write_freq(300_000, false, &mut file_handler).is_ok());
+            assert_eq!(info.write_failure.as_ref().unwrap().failures, failures);
+            info.write_failure.as_mut().unwrap().retry_at = Instant::now();
+        }
+
+        assert!(info.write_freq(300_000, false, &mut file_handler).is_err());
+        assert!(info.write_error().is_some());
+
+        fs::remove_dir(path.join("scaling_max_freq")).unwrap();
+        fs::write(path.join("scaling_max_freq"), "").unwrap();
+        info.write_failure.as_mut().unwrap().retry_at = Instant::now();
+        info.write_freq(300_000, false, &mut file_handler).unwrap();
+        assert!(info.write_failure.is_none());
     }
 }
@@ -20,23 +20,26 @@ pub mod extra_policy;
 mod process_monitor;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs,
     path::Path,
-    sync::{OnceLock, atomic::AtomicBool},
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result};
 #[cfg(debug_assertions)]
 use log::debug;
-use log::warn;
+use log::{error, info, warn};
 use parking_lot::Mutex;
 use process_monitor::ProcessMonitor;
 
 use crate::{
-    Extension,
+    Config, Extension,
     api::{trigger_init_cpu_freq, trigger_reset_cpu_freq},
     file_handler::FileHandler,
 };
@@ -45,10 +48,23 @@ use extra_policy::ExtraPolicy;
 
 pub static EXTRA_POLICY_MAP: OnceLock<HashMap<i32, Mutex<ExtraPolicy>>> = OnceLock::new();
 pub static IGNORE_MAP: OnceLock<HashMap<i32, AtomicBool>> = OnceLock::new();
+// Grouped policy -> the policy that controls its group
+pub static GROUP_LEADERS: OnceLock<HashMap<i32, i32>> = OnceLock::new();
+static GROUP_CONSTRAINT_REPORTED: AtomicBool = AtomicBool::new(false);
+// Boost frequencies stay selectable this long after a game starts
+const LAUNCH_BOOST_WINDOW: Duration = Duration::from_secs(30);
+static CIRCULAR_REPORTED: AtomicBool = AtomicBool::new(false);
 
 #[derive(Debug)]
 pub struct Controller {
     max_freq: isize,
+    use_boost_freqs: bool,
+    min_freq_per: u8,
+    max_freq_per: u8,
+    policy_max_freq_per: HashMap<i32, u8>,
+    freq_ramp_step: usize,
+    launch_boost_until: Option<Instant>,
+    throttle_ceilings: HashMap<i32, isize>,
     cpu_infos: Vec<Info>,
     file_handler: FileHandler,
     process_monitor: ProcessMonitor,
@@ -56,7 +72,7 @@ pub struct Controller {
 }
 
 impl Controller {
-    pub fn new() -> Result<Self> {
+    pub fn new(config: &mut Config) -> Result<Self> {
         let mut cpu_infos = Self::load_cpu_infos()?;
         cpu_infos.sort_by_key(|cpu| cpu.policy);
 
@@ -73,6 +89,15 @@ impl Controller {
                 .collect()
         });
 
+        let skip_lowest_opps = config.config().skip_lowest_opps;
+        for cpu in &mut cpu_infos {
+            cpu.skip_lowest_freqs(skip_lowest_opps);
+        }
+
+        if config.config().group_policies {
+            cpu_infos = Self::group_cpu_infos(cpu_infos);
+        }
+
         #[cfg(debug_assertions)]
         debug!("cpu infos: {:?}", cpu_infos);
 
@@ -83,13 +108,23 @@ impl Controller {
             .copied()
             .unwrap_or(0);
 
-        Ok(Self {
+        let mut controller = Self {
             max_freq,
+            use_boost_freqs: false,
+            min_freq_per: 0,
+            max_freq_per: 100,
+            policy_max_freq_per: HashMap::new(),
+            freq_ramp_step: 0,
+            launch_boost_until: None,
+            throttle_ceilings: HashMap::new(),
             cpu_infos,
             file_handler: FileHandler::new(),
             process_monitor: ProcessMonitor::new(),
             util_max: None,
-        })
+        };
+        controller.apply_config(config);
+
+        Ok(controller)
     }
 
     fn load_cpu_infos() -> Result<Vec<Info>> {
@@ -122,6 +157,28 @@ impl Controller {
         Ok(cpu_infos)
     }
 
+    fn group_cpu_infos(cpu_infos: Vec<Info>) -> Vec<Info> {
+        let mut grouped: Vec<Info> = Vec::new();
+        let mut leaders = HashMap::new();
+
+        for cpu in cpu_infos {
+            match grouped.last_mut() {
+                Some(last) if last.can_group_with(&cpu) => {
+                    leaders.insert(cpu.policy, last.policy);
+                    last.group_with(cpu);
+                }
+                _ => grouped.push(cpu),
+            }
+        }
+        GROUP_LEADERS.get_or_init(|| leaders);
+
+        for cpu in &grouped {
+            info!("policy{} controls cpus {:?}", cpu.policy, cpu.cpus());
+        }
+
+        grouped
+    }
+
     fn retry_load_info(path: &Path) -> Info {
         loop {
             match Info::new(path) {
@@ -137,6 +194,7 @@ impl Controller {
 
     pub fn init_game(&mut self, pid: i32, extension: &Extension) {
         trigger_init_cpu_freq(extension);
+        self.launch_boost_until = Some(Instant::now() + LAUNCH_BOOST_WINDOW);
         self.set_all_cpu_freq(self.max_freq);
         self.process_monitor.set_pid(Some(pid));
         self.util_max = None;
@@ -144,19 +202,79 @@ impl Controller {
 
     pub fn init_default(&mut self, extension: &Extension) {
         trigger_reset_cpu_freq(extension);
+        self.launch_boost_until = None;
         self.reset_all_cpu_freq();
         self.process_monitor.set_pid(None);
         self.util_max = None;
     }
 
+    // Reloadable keys, the rest need a restart
+    pub fn apply_config(&mut self, config: &mut Config) {
+        let config = config.config();
+        let rewrite_interval = Duration::from_secs(config.freq_rewrite_interval);
+
+        self.use_boost_freqs = config.use_boost_freqs;
+        self.freq_ramp_step = config.freq_ramp_step;
+        for cpu in &mut self.cpu_infos {
+            cpu.set_rewrite_interval(rewrite_interval);
+        }
+    }
+
+    pub const fn set_freq_per(&mut self, min_freq_per: u8, max_freq_per: u8) {
+        self.min_freq_per = min_freq_per;
+        self.max_freq_per = max_freq_per;
+    }
+
+    // Per-policy max_freq_per overrides
+    pub fn set_policy_max_freq_per(&mut self, overrides: impl Iterator<Item = (i32, u8)>) {
+        self.policy_max_freq_per.clear();
+        self.policy_max_freq_per.extend(overrides);
+    }
+
+    // Follow the kernel's cooling limit
+    pub fn set_throttle_states(&mut self, states: impl Iterator<Item = (usize, u64)>) {
+        let mut ceilings = HashMap::new();
+
+        for (cpu, state) in states {
+            let Some(info) = self
+                .cpu_infos
+                .iter()
+                .find(|info| info.cpus().contains(&cpu) || info.policy as usize == cpu)
+            else {
+                continue;
+            };
+            if let Some(ceiling) = info.confirmed_throttle_ceiling(state) {
+                ceilings.insert(info.policy, ceiling);
+            }
+        }
+
+        for (policy, ceiling) in &ceilings {
+            if self.throttle_ceilings.get(policy) != Some(ceiling) {
+                info!("policy{policy} throttled by kernel, ceiling {ceiling}khz");
+            }
+        }
+        for policy in self.throttle_ceilings.keys() {
+            if !ceilings.contains_key(policy) {
+                info!("policy{policy} throttle ceiling released");
+            }
+        }
+
+        self.throttle_ceilings = ceilings;
+    }
+
     pub fn fas_update_freq(&mut self, control: isize, is_janked: bool) {
         #[cfg(debug_assertions)]
         debug!("change freq: {}", control);
 
         let fas_freqs = self.compute_target_frequencies(control, is_janked);
-        let sorted_policies = self.sort_policies_topologically();
+        let (sorted_policies, cyclic) = self.sort_policies_topologically();
         let fas_freqs = Self::apply_absolute_constraints(fas_freqs, &sorted_policies);
-        let fas_freqs = Self::apply_relative_constraints(fas_freqs, &sorted_policies);
+        let fas_freqs = Self::apply_relative_constraints(fas_freqs, &sorted_policies, &cyclic);
+        let allow_boost = self.use_boost_freqs
+            || is_janked
+            || self
+                .launch_boost_until
+                .is_some_and(|until| Instant::now() < until);
 
         if no_extra_policy() {
             let fas_freq_max = fas_freqs.values().max().copied().unwrap();
@@ -166,13 +284,45 @@ impl Controller {
                         fas_freq_max.saturating_sub(100_000),
                         fas_freq_max.saturating_add(100_000),
                     );
-                    let _ = cpu.write_freq(freq, &mut self.file_handler);
+                    let max_freq_per = self
+                        .policy_max_freq_per
+                        .get(&cpu.policy)
+                        .copied()
+                        .unwrap_or(self.max_freq_per);
+                    let freq =
+                        cpu.clamp_freq_per(freq, self.min_freq_per, max_freq_per, allow_boost);
+                    let freq = self
+                        .throttle_ceilings
+                        .get(&cpu.policy)
+                        .map_or(freq, |ceiling| freq.min(*ceiling));
+                    let _ = cpu.write_freq_ramped(
+                        freq,
+                        allow_boost,
+                        self.freq_ramp_step,
+                        &mut self.file_handler,
+                    );
                 }
             }
         } else {
             for cpu in &mut self.cpu_infos {
                 if let Some(freq) = fas_freqs.get(&cpu.policy).copied() {
-                    let _ = cpu.write_freq(freq, &mut self.file_handler);
+                    let max_freq_per = self
+                        .policy_max_freq_per
+                        .get(&cpu.policy)
+                        .copied()
+                        .unwrap_or(self.max_freq_per);
+                    let freq =
+                        cpu.clamp_freq_per(freq, self.min_freq_per, max_freq_per, allow_boost);
+                    let freq = self
+                        .throttle_ceilings
+                        .get(&cpu.policy)
+                        .map_or(freq, |ceiling| freq.min(*ceiling));
+                    let _ = cpu.write_freq_ramped(
+                        freq,
+                        allow_boost,
+                        self.freq_ramp_step,
+                        &mut self.file_handler,
+                    );
                 }
             }
         }
@@ -198,7 +348,7 @@ impl Controller {
         let cur_freq_max = self
             .cpu_infos
             .iter()
-            .map(cpu_info::Info::read_freq)
+            .filter_map(|cpu| cpu.read_freq().ok())
             .max()
             .unwrap_or_default();
 
@@ -237,56 +387,36 @@ impl Controller {
             .collect()
     }
 
-    fn sort_policies_topologically(&self) -> Vec<i32> {
-        let mut graph: HashMap<_, Vec<_>> = HashMap::new();
-        let mut indegree: HashMap<_, _> = HashMap::new();
-
-        for cpu in &self.cpu_infos {
-            let policy = cpu.policy;
-
-            if let ExtraPolicy::RelRangeBound(ref rel_bound) = *EXTRA_POLICY_MAP
-                .get()
-                .context("EXTRA_POLICY_MAP not initialized")
-                .unwrap()
-                .get(&policy)
-                .context("CPU Policy not found")
-                .unwrap()
-                .lock()
-            {
-                graph.entry(rel_bound.rel_to).or_default().push(policy);
-                *indegree.entry(policy).or_insert(0) += 1;
-            }
-
-            indegree.entry(policy).or_insert(0);
-        }
-
-        let mut queue: Vec<_> = indegree
+    fn sort_policies_topologically(&self) -> (Vec<i32>, HashSet<i32>) {
+        let extra_policy_map = EXTRA_POLICY_MAP
+            .get()
+            .context("EXTRA_POLICY_MAP not initialized")
+            .unwrap();
+        let rel_to: Vec<_> = self
+            .cpu_infos
             .iter()
-            .filter(|&(_, &deg)| deg == 0)
-            .map(|(&policy, _)| policy)
+            .map(|cpu| {
+                let rel_to = match *extra_policy_map
+                    .get(&cpu.policy)
+                    .context("CPU Policy not found")
+                    .unwrap()
+                    .lock()
+                {
+                    ExtraPolicy::RelRangeBound(ref rel_bound) => Some(rel_bound.rel_to),
+                    _ => None,
+                };
+                (cpu.policy, rel_to)
+            })
             .collect();
-        let mut sorted_policies = Vec::new();
-
-        while let Some(policy) = queue.pop() {
-            sorted_policies.push(policy);
-            if let Some(dependents) = graph.get(&policy) {
-                for &dependent in dependents {
-                    if let Some(deg) = indegree.get_mut(&dependent) {
-                        *deg -= 1;
-                        if *deg == 0 {
-                            queue.push(dependent);
-                        }
-                    }
-                }
-            }
-        }
 
-        assert!(
-            (sorted_policies.len() >= indegree.len()),
-            "Circular dependency detected in CPU policies"
-        );
+        let (sorted_policies, cyclic) = topological_order(&rel_to);
+        if !cyclic.is_empty() && !CIRCULAR_REPORTED.swap(true, Ordering::Relaxed) {
+            error!(
+                "Circular dependency detected in CPU policies {cyclic:?}, ignoring their relative bounds"
+            );
+        }
 
-        sorted_policies
+        (sorted_policies, cyclic)
     }
 
     fn apply_absolute_constraints(
@@ -319,8 +449,12 @@ impl Controller {
     fn apply_relative_constraints(
         mut fas_freqs: HashMap<i32, isize>,
         sorted_policies: &[i32],
+        cyclic: &HashSet<i32>,
     ) -> HashMap<i32, isize> {
-        for policy in sorted_policies {
+        for policy in sorted_policies
+            .iter()
+            .filter(|policy| !cyclic.contains(policy))
+        {
             if let Some(freq) = fas_freqs.get(policy).copied() {
                 let adjusted_freq = match *EXTRA_POLICY_MAP
                     .get()
@@ -360,12 +494,18 @@ impl Controller {
 
     fn set_all_cpu_freq(&mut self, freq: isize) {
         for cpu in &mut self.cpu_infos {
-            let _ = cpu.write_freq(freq, &mut self.file_handler);
+            let max_freq_per = self
+                .policy_max_freq_per
+                .get(&cpu.policy)
+                .copied()
+                .unwrap_or(self.max_freq_per);
+            let freq = cpu.clamp_freq_per(freq, self.min_freq_per, max_freq_per, true);
+            let _ = cpu.write_freq(freq, true, &mut self.file_handler);
         }
     }
 
     fn reset_all_cpu_freq(&mut self) {
-        for cpu in &self.cpu_infos {
+        for cpu in &mut self.cpu_infos {
             let _ = cpu.reset_freq(&mut self.file_handler);
         }
     }
@@ -373,6 +513,37 @@ impl Controller {
     pub fn util_max(&self) -> f64 {
         self.util_max.unwrap_or_default()
     }
+
+    pub const fn max_freq(&self) -> isize {
+        self.max_freq
+    }
+
+    pub fn freq_load(&self) -> f64 {
+        let cur_fas_freq_max = self
+            .cpu_infos
+            .iter()
+            .map(|cpu| cpu.cur_fas_freq)
+            .max()
+            .unwrap_or_default();
+
+        if self.max_freq > 0 {
+            cur_fas_freq_max as f64 / self.max_freq as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub fn cpu_freqs(&self) -> impl Iterator<Item = (i32, isize)> + '_ {
+        self.cpu_infos
+            .iter()
+            .map(|cpu| (cpu.policy, cpu.cur_fas_freq))
+    }
+
+    pub fn write_errors(&self) -> impl Iterator<Item = (i32, &str)> + '_ {
+        self.cpu_infos
+            .iter()
+            .filter_map(|cpu| Some((cpu.policy, cpu.write_error()?)))
+    }
 }
 
 fn no_extra_policy() -> bool {
@@ -383,3 +554,89 @@ fn no_extra_policy() -> bool {
         .values()
         .all(|policy| *policy.lock() == ExtraPolicy::None)
 }
+
+// Policies in a cycle go last
+fn topological_order(rel_to: &[(i32, Option<i32>)]) -> (Vec<i32>, HashSet<i32>) {
+    let mut graph: HashMap<_, Vec<_>> = HashMap::new();
+    let mut indegree: HashMap<_, _> = HashMap::new();
+
+    for &(policy, rel_to) in rel_to {
+        if let Some(rel_to) = rel_to {
+            graph.entry(rel_to).or_default().push(policy);
+            *indegree.entry(policy).or_insert(0) += 1;
+        }
+
+        indegree.entry(policy).or_insert(0);
+    }
+
+    let mut queue: Vec<_> = indegree
+        .iter()
+        .filter(|&(_, &deg)| deg == 0)
+        .map(|(&policy, _)| policy)
+        .collect();
+    let mut sorted_policies = Vec::new();
+
+    while let Some(policy) = queue.pop() {
+        sorted_policies.push(policy);
+        if let Some(dependents) = graph.get(&policy) {
+            for &dependent in dependents {
+                if let Some(deg) = indegree.get_mut(&dependent) {
+                    *deg -= 1;
+                    if *deg == 0 {
+                        queue.push(dependent);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut cyclic: Vec<_> = indegree
+        .into_iter()
+        .filter(|&(_, deg)| deg > 0)
+        .map(|(policy, _)| policy)
+        .collect();
+    cyclic.sort_unstable();
+    sorted_policies.extend_from_slice(&cyclic);
+
+    (sorted_policies, cyclic.into_iter().collect())
+}
+
+// Constraints on a grouped policy move to its leader
+pub fn group_leader(policy: i32) -> i32 {
+    let Some(leader) = GROUP_LEADERS
+        .get()
+        .and_then(|leaders| leaders.get(&policy))
+        .copied()
+    else {
+        return policy;
+    };
+
+    if !GROUP_CONSTRAINT_REPORTED.swap(true, Ordering::Relaxed) {
+        warn!(
+            "policy{policy} is grouped under policy{leader}, constraints on grouped policies apply to the whole group"
+        );
+    }
+
+    leader
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dependents_follow_the_policy_they_are_bound_to() {
+        let (order, cyclic) = topological_order(&[(0, None), (4, Some(0)), (7, Some(4))]);
+
+        assert_eq!(order, vec![0, 4, 7]);
+        assert!(cyclic.is_empty());
+    }
+
+    #[test]
+    fn cycle_members_stay_in_the_order() {
+        let (order, cyclic) = topological_order(&[(0, None), (4, Some(7)), (7, Some(4))]);
+
+        assert_eq!(order, vec![0, 4, 7]);
+        assert_eq!(cyclic, HashSet::from([4, 7]));
+    }
+}
@@ -28,8 +28,9 @@ use std::{
     time::{Duration, Instant},
 };
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use libc::{_SC_CLK_TCK, sysconf};
+use log::trace;
 
 #[derive(Debug, Clone, Copy)]
 struct UsageTracker {
@@ -54,8 +55,17 @@ impl UsageTracker {
         let new_cputime = get_thread_cpu_time(self.pid, self.tid)?;
         let elapsed_ticks = self.read_timer.elapsed().as_secs_f64() * tick_per_sec as f64;
         self.read_timer = Instant::now();
-        let cputime_slice = new_cputime - self.last_cputime;
+        let last_cputime = self.last_cputime;
         self.last_cputime = new_cputime;
+
+        // Reused tid or bogus read, skip this tick
+        let Some(cputime_slice) = new_cputime.checked_sub(last_cputime) else {
+            trace!(
+                "cputime of tid {} went backwards: {last_cputime} -> {new_cputime}",
+                self.tid
+            );
+            bail!("cputime went backwards");
+        };
         Ok(cputime_slice as f64 / elapsed_ticks)
     }
 }
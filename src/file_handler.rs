@@ -21,20 +21,32 @@ use std::{
     io::{self, ErrorKind, prelude::*},
     os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Result;
 use sys_mount::{UnmountFlags, unmount};
 
+const WOULD_BLOCK_RETRIES: u32 = 3;
+const WOULD_BLOCK_BACKOFF: Duration = Duration::from_millis(10);
+
+#[derive(Debug)]
+struct Retry {
+    attempts: u32,
+    at: Instant,
+}
+
 #[derive(Debug)]
 pub struct FileHandler {
     files: HashMap<PathBuf, File>,
+    retries: HashMap<PathBuf, Retry>,
 }
 
 impl FileHandler {
     pub fn new() -> Self {
         Self {
             files: HashMap::new(),
+            retries: HashMap::new(),
         }
     }
 
@@ -61,21 +73,57 @@ impl FileHandler {
         path: impl AsRef<Path>,
         content: impl AsRef<[u8]>,
     ) -> Result<()> {
-        if let Err(e) = self.write(path.as_ref(), content.as_ref()) {
-            match e.kind() {
-                ErrorKind::PermissionDenied => {
-                    set_permissions(path.as_ref(), PermissionsExt::from_mode(0o644))?;
-                    self.write(path, content)?;
-                    Ok(())
+        let path = path.as_ref();
+        if self
+            .retries
+            .get(path)
+            .is_some_and(|retry| Instant::now() < retry.at)
+        {
+            return Err(io::Error::from(ErrorKind::WouldBlock).into());
+        }
+
+        let result = match self.write(path, content.as_ref()) {
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+                set_permissions(path, PermissionsExt::from_mode(0o644))?;
+                self.write(path, content)
+            }
+            Err(e) if e.kind() == ErrorKind::InvalidInput => Ok(()),
+            result => result,
+        };
+
+        match result {
+            Ok(()) => {
+                self.retries.remove(path);
+                Ok(())
+            }
+            // Busy on some GKI kernels
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                let attempts = self.retries.get(path).map_or(0, |retry| retry.attempts);
+                match retry_delay(attempts, jitter()) {
+                    Some(delay) => {
+                        self.retries.insert(
+                            path.to_path_buf(),
+                            Retry {
+                                attempts: attempts + 1,
+                                at: Instant::now() + delay,
+                            },
+                        );
+                    }
+                    None => {
+                        self.retries.remove(path);
+                    }
                 }
-                ErrorKind::InvalidInput => Ok(()),
-                _ => Err(e.into()),
+                Err(e.into())
             }
-        } else {
-            Ok(())
+            Err(e) => Err(e.into()),
         }
     }
 
+    // A failed write that is still being retried is not a failure yet
+    pub fn retry_pending(&self, path: impl AsRef<Path>) -> bool {
+        self.retries.contains_key(path.as_ref())
+    }
+
     pub fn write(&mut self, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> io::Result<()> {
         match self.files.entry(path.as_ref().to_path_buf()) {
             Entry::Occupied(mut entry) => {
@@ -93,3 +141,76 @@ impl FileHandler {
         Ok(())
     }
 }
+
+fn retry_delay(attempts: u32, jitter: u32) -> Option<Duration> {
+    (attempts < WOULD_BLOCK_RETRIES).then(|| {
+        let delay = WOULD_BLOCK_BACKOFF * 2u32.pow(attempts);
+        delay + delay * (jitter % 100) / 200
+    })
+}
+
+fn jitter() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_micros())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        time::{Duration, Instant},
+    };
+
+    use super::{FileHandler, Retry, WOULD_BLOCK_BACKOFF, WOULD_BLOCK_RETRIES, retry_delay};
+    use crate::test_utils::fixture_dir;
+
+    #[test]
+    fn would_block_backs_off_with_jitter_until_the_budget_is_spent() {
+        for attempts in 0..WOULD_BLOCK_RETRIES {
+            let base = WOULD_BLOCK_BACKOFF * 2u32.pow(attempts);
+            assert_eq!(retry_delay(attempts, 0), Some(base));
+            let jittered = retry_delay(attempts, 99).unwrap();
+            assert!(jittered > base && jittered < base * 3 / 2);
+        }
+
+        assert_eq!(retry_delay(WOULD_BLOCK_RETRIES, 0), None);
+    }
+
+    #[test]
+    fn pending_retry_defers_the_write_to_a_later_call() {
+        let path = fixture_dir("file-handler/deferred").join("scaling_max_freq");
+        fs::write(&path, "").unwrap();
+        let mut file_handler = FileHandler::new();
+        file_handler.retries.insert(
+            path.clone(),
+            Retry {
+                attempts: 1,
+                at: Instant::now() + Duration::from_secs(60),
+            },
+        );
+
+        assert!(file_handler.write_with_workround(&path, "1000000").is_err());
+        assert!(file_handler.retry_pending(&path));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+    }
+
+    #[test]
+    fn due_retry_writes_and_clears_the_pending_state() {
+        let path = fixture_dir("file-handler/due").join("scaling_max_freq");
+        fs::write(&path, "").unwrap();
+        let mut file_handler = FileHandler::new();
+        file_handler.retries.insert(
+            path.clone(),
+            Retry {
+                attempts: 2,
+                at: Instant::now(),
+            },
+        );
+
+        file_handler.write_with_workround(&path, "1000000").unwrap();
+
+        assert!(!file_handler.retry_pending(&path));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1000000");
+    }
+}
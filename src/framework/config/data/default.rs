@@ -15,7 +15,9 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use super::Config;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use super::{Config, FreqPer, ModeConfig};
 
 impl Config {
     pub const fn default_value_keep_std() -> bool {
@@ -25,4 +27,86 @@ impl Config {
     pub const fn default_value_scene_game_list() -> bool {
         true
     }
+
+    pub const fn default_value_group_policies() -> bool {
+        false
+    }
+
+    pub const fn default_value_use_boost_freqs() -> bool {
+        false
+    }
+
+    pub const fn default_value_skip_lowest_opps() -> usize {
+        0
+    }
+
+    pub const fn default_value_freq_ramp_step() -> usize {
+        0
+    }
+
+    pub const fn default_value_freq_rewrite_interval() -> u64 {
+        5
+    }
+
+    pub const fn default_value_external_frametime() -> bool {
+        false
+    }
+
+    pub const fn default_value_min_target_fps() -> u32 {
+        10
+    }
+
+    pub const fn default_value_min_target_frames() -> usize {
+        0
+    }
+
+    pub const fn default_value_fallback_target_fps() -> u32 {
+        0
+    }
+
+    pub const fn default_value_merge_short_frames() -> bool {
+        false
+    }
+
+    pub const fn default_value_battery_cap_thresh() -> u8 {
+        0
+    }
+
+    pub const fn default_value_battery_cap_floor() -> u8 {
+        60
+    }
+
+    pub const fn default_value_auto_mode() -> bool {
+        false
+    }
+
+    pub const fn default_value_auto_mode_dwell() -> u64 {
+        10
+    }
+
+    pub const fn default_value_trace_marker() -> bool {
+        false
+    }
+
+    pub const fn default_value_loop_jitter_warn() -> f64 {
+        0.5
+    }
+
+    pub const fn default_value_metrics() -> bool {
+        false
+    }
+
+    pub const fn default_value_metrics_addr() -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9091)
+    }
+}
+
+impl ModeConfig {
+    pub const fn default_value_min_freq_per() -> u8 {
+        0
+    }
+
+    pub const fn default_value_max_freq_per() -> FreqPer {
+        FreqPer::BaseOnly(100)
+    }
 }
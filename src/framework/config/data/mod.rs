@@ -17,7 +17,10 @@
 
 mod default;
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+};
 
 use serde::{Deserialize, Serialize};
 use toml::Table;
@@ -41,12 +44,54 @@ pub struct Config {
     pub keep_std: bool,
     #[serde(default = "Config::default_value_scene_game_list")]
     pub scene_game_list: bool,
+    #[serde(default = "Config::default_value_group_policies")]
+    pub group_policies: bool,
+    #[serde(default = "Config::default_value_use_boost_freqs")]
+    pub use_boost_freqs: bool,
+    #[serde(default = "Config::default_value_skip_lowest_opps")]
+    pub skip_lowest_opps: usize,
+    #[serde(default = "Config::default_value_freq_ramp_step")]
+    pub freq_ramp_step: usize,
+    #[serde(default = "Config::default_value_freq_rewrite_interval")]
+    pub freq_rewrite_interval: u64,
+    #[serde(default = "Config::default_value_external_frametime")]
+    pub external_frametime: bool,
+    #[serde(default = "Config::default_value_min_target_fps")]
+    pub min_target_fps: u32,
+    #[serde(default = "Config::default_value_min_target_frames")]
+    pub min_target_frames: usize,
+    #[serde(default = "Config::default_value_fallback_target_fps")]
+    pub fallback_target_fps: u32,
+    #[serde(default = "Config::default_value_merge_short_frames")]
+    pub merge_short_frames: bool,
+    #[serde(default = "Config::default_value_battery_cap_thresh")]
+    pub battery_cap_thresh: u8,
+    #[serde(default = "Config::default_value_battery_cap_floor")]
+    pub battery_cap_floor: u8,
+    #[serde(default = "Config::default_value_auto_mode")]
+    pub auto_mode: bool,
+    #[serde(default = "Config::default_value_auto_mode_dwell")]
+    pub auto_mode_dwell: u64,
+    #[serde(default = "Config::default_value_trace_marker")]
+    pub trace_marker: bool,
+    #[serde(default = "Config::default_value_loop_jitter_warn")]
+    pub loop_jitter_warn: f64,
+    #[serde(default = "Config::default_value_metrics")]
+    pub metrics: bool,
+    #[serde(default = "Config::default_value_metrics_addr")]
+    pub metrics_addr: SocketAddr,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ModeConfig {
     pub margin_fps: MarginFps,
     pub core_temp_thresh: TemperatureThreshold,
+    #[serde(default = "ModeConfig::default_value_min_freq_per")]
+    pub min_freq_per: u8,
+    #[serde(default = "ModeConfig::default_value_max_freq_per")]
+    pub max_freq_per: FreqPer,
+    #[serde(skip)]
+    pub policy_max_freq_per: Vec<(i32, u8)>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -69,6 +114,62 @@ pub enum MarginFps {
     },
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum FreqPer {
+    #[serde(untagged)]
+    BaseOnly(u8),
+    #[serde(untagged)]
+    Advanced {
+        base: u8,
+        #[serde(flatten)]
+        overrides: HashMap<String, u8>,
+    },
+}
+
+impl ConfigData {
+    pub fn resolve_policy_max_freq_per(&mut self) {
+        for mode_config in [
+            &mut self.powersave,
+            &mut self.balance,
+            &mut self.performance,
+            &mut self.fast,
+        ] {
+            mode_config.policy_max_freq_per = mode_config.max_freq_per.policy_overrides().collect();
+        }
+    }
+}
+
+impl FreqPer {
+    pub fn parse_policy_key(key: &str) -> Option<i32> {
+        let id = key.trim().strip_prefix("policy")?;
+        if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        id.parse().ok()
+    }
+
+    pub fn policy_overrides(&self) -> impl Iterator<Item = (i32, u8)> + '_ {
+        self.overrides()
+            .filter_map(|(key, per)| Some((Self::parse_policy_key(key)?, *per)))
+    }
+
+    fn overrides(&self) -> impl Iterator<Item = (&String, &u8)> {
+        match self {
+            Self::BaseOnly(_) => None,
+            Self::Advanced { overrides, .. } => Some(overrides),
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    pub fn invalid_policy_keys(&self) -> impl Iterator<Item = &str> {
+        self.overrides()
+            .map(|(key, _)| key.as_str())
+            .filter(|key| Self::parse_policy_key(key).is_none())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub enum MarginFpsValue {
     #[serde(untagged)]
@@ -100,3 +201,67 @@ pub struct SceneApp {
     #[serde(rename = "@value")]
     pub is_game: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::FreqPer;
+
+    #[derive(Deserialize)]
+    struct ModeFixture {
+        max_freq_per: FreqPer,
+    }
+
+    fn parse(toml: &str) -> FreqPer {
+        toml::from_str::<ModeFixture>(toml).unwrap().max_freq_per
+    }
+
+    #[test]
+    fn base_only_freq_per_is_parsed() {
+        let freq_per = parse("max_freq_per = 80\n");
+
+        assert!(matches!(freq_per, FreqPer::BaseOnly(80)));
+        assert_eq!(freq_per.invalid_policy_keys().count(), 0);
+    }
+
+    #[test]
+    fn advanced_freq_per_is_parsed() {
+        let freq_per = parse("max_freq_per = { policy7 = 70, base = 100, policy4 = 85 }\n");
+
+        let FreqPer::Advanced { base, overrides } = &freq_per else {
+            panic!("expected the per-policy format, got {freq_per:?}");
+        };
+        assert_eq!(*base, 100);
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides.get("policy4"), Some(&85));
+        assert_eq!(overrides.get("policy7"), Some(&70));
+        assert_eq!(freq_per.invalid_policy_keys().count(), 0);
+
+        let mut policy_overrides: Vec<_> = freq_per.policy_overrides().collect();
+        policy_overrides.sort_unstable();
+        assert_eq!(policy_overrides, [(4, 85), (7, 70)]);
+    }
+
+    #[test]
+    fn misspelled_policy_keys_are_reported() {
+        let freq_per =
+            parse("max_freq_per = { base = 100, polcy4 = 85, policy4x = 80, policy7 = 70 }\n");
+
+        let mut invalid: Vec<_> = freq_per.invalid_policy_keys().collect();
+        invalid.sort_unstable();
+        assert_eq!(invalid, ["polcy4", "policy4x"]);
+    }
+
+    #[test]
+    fn policy_keys_are_parsed_strictly() {
+        assert_eq!(FreqPer::parse_policy_key("policy0"), Some(0));
+        assert_eq!(FreqPer::parse_policy_key(" policy7 "), Some(7));
+        assert_eq!(FreqPer::parse_policy_key("policy"), None);
+        assert_eq!(FreqPer::parse_policy_key("polcy4"), None);
+        assert_eq!(FreqPer::parse_policy_key("policy4x"), None);
+        assert_eq!(FreqPer::parse_policy_key("policy+4"), None);
+        assert_eq!(FreqPer::parse_policy_key("policy-1"), None);
+        assert_eq!(FreqPer::parse_policy_key("cpu4"), None);
+    }
+}
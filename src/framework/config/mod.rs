@@ -27,7 +27,9 @@ use log::{error, info};
 use toml::Value;
 
 use crate::framework::{error::Result, node::Mode};
-pub use data::{Config as ConfigConfig, ConfigData, MarginFps, ModeConfig, TemperatureThreshold};
+pub use data::{
+    Config as ConfigConfig, ConfigData, FreqPer, MarginFps, ModeConfig, TemperatureThreshold,
+};
 use read::wait_and_read;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -46,7 +48,8 @@ impl Config {
         let path = p.as_ref();
         let std_path = sp.as_ref();
         let toml_raw = fs::read_to_string(path)?;
-        let toml: ConfigData = toml::from_str(&toml_raw)?;
+        let mut toml: ConfigData = toml::from_str(&toml_raw)?;
+        toml.resolve_policy_max_freq_per();
 
         let (sx, rx) = mpsc::channel();
         let inner = Inner::new(toml, rx);
@@ -130,4 +133,9 @@ impl Config {
     pub fn config(&mut self) -> ConfigConfig {
         self.inner.config().config
     }
+
+    // Bumped on every reload
+    pub fn generation(&mut self) -> u64 {
+        self.inner.generation()
+    }
 }
@@ -18,16 +18,18 @@
 use std::{fs, path::Path, sync::mpsc::Sender, time::Duration};
 
 use inotify::{Inotify, WatchMask};
-use log::{debug, error};
+use log::{debug, error, info, warn};
 
 use super::data::{ConfigData, SceneAppList};
 use crate::framework::error::Result;
 
 const SCENE_PROFILE: &str = "/data/data/com.omarea.vtools/shared_prefs/games.xml";
 const MAX_RETRY_COUNT: u8 = 10;
+const MAX_OUTAGE_BACKOFF: Duration = Duration::from_secs(30);
 
 pub(super) fn wait_and_read(path: &Path, std_path: &Path, sx: &Sender<ConfigData>) -> Result<()> {
     let std_config = read_config(std_path)?;
+    let mut user_config_loaded = false;
 
     loop {
         match read_config_with_retry(path) {
@@ -38,6 +40,12 @@ pub(super) fn wait_and_read(path: &Path, std_path: &Path, sx: &Sender<ConfigData
                     }
                 }
                 sx.send(config).unwrap();
+                user_config_loaded = true;
+            }
+            Err(e) if user_config_loaded => {
+                // /sdcard can go away for a while, keep the last good config
+                error!("Failed to re-read config: {}", e);
+                error!("Keeping the current config until user config is available.");
             }
             Err(e) => {
                 error!("Too many retries reading config: {}", e);
@@ -52,10 +60,26 @@ pub(super) fn wait_and_read(path: &Path, std_path: &Path, sx: &Sender<ConfigData
 
 fn read_config(path: &Path) -> Result<ConfigData> {
     let content = fs::read_to_string(path)?;
-    let config = toml::from_str(&content)?;
+    let mut config: ConfigData = toml::from_str(&content)?;
+    warn_invalid_policy_keys(path, &config);
+    config.resolve_policy_max_freq_per();
     Ok(config)
 }
 
+// Misspelled override keys would be ignored silently
+fn warn_invalid_policy_keys(path: &Path, config: &ConfigData) {
+    for (mode, mode_config) in [
+        ("powersave", &config.powersave),
+        ("balance", &config.balance),
+        ("performance", &config.performance),
+        ("fast", &config.fast),
+    ] {
+        for key in mode_config.max_freq_per.invalid_policy_keys() {
+            warn!("Ignoring max_freq_per key {key:?} of {mode} in {path:?}, expected policy<N>");
+        }
+    }
+}
+
 fn read_config_with_retry(path: &Path) -> Result<ConfigData> {
     let mut retry_count = 0;
 
@@ -100,12 +124,28 @@ fn wait_until_update(path: &Path) -> Result<()> {
             .add(SCENE_PROFILE, WatchMask::MODIFY | WatchMask::CLOSE_WRITE)?;
     }
 
-    inotify
+    if let Err(e) = inotify
         .watches()
-        .add(path, WatchMask::MODIFY | WatchMask::CLOSE_WRITE)?;
+        .add(path, WatchMask::MODIFY | WatchMask::CLOSE_WRITE)
+    {
+        warn!("Config at {:?} is unavailable: {}", path, e);
+        wait_until_available(path);
+        return Ok(());
+    }
 
     let mut buffer = [0; 1024];
     inotify.read_events_blocking(&mut buffer)?;
 
     Ok(())
 }
+
+fn wait_until_available(path: &Path) {
+    let mut backoff = Duration::from_secs(1);
+
+    while !path.exists() {
+        std::thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_OUTAGE_BACKOFF);
+    }
+
+    info!("Config at {:?} is available again", path);
+}
@@ -25,11 +25,13 @@ use log::warn;
 use crate::cpu_common::{
     EXTRA_POLICY_MAP, IGNORE_MAP,
     extra_policy::{AbsRangeBound, ExtraPolicy, RelRangeBound},
+    group_leader,
 };
 
 static WARNING_FLAG: AtomicBool = AtomicBool::new(false);
 
 pub fn remove_extra_policy(policy: i32) {
+    let policy = group_leader(policy);
     *EXTRA_POLICY_MAP
         .get()
         .context("EXTRA_POLICY_MAP not initialized")
@@ -41,6 +43,7 @@ pub fn remove_extra_policy(policy: i32) {
 }
 
 pub fn set_extra_policy_abs(policy: i32, min: Option<isize>, max: Option<isize>) {
+    let policy = group_leader(policy);
     let extra_policy = if min.is_none() && max.is_none() {
         ExtraPolicy::None
     } else {
@@ -66,7 +69,12 @@ pub fn set_extra_policy_rel(
     min: Option<isize>,
     max: Option<isize>,
 ) {
-    let extra_policy = if min.is_none() && max.is_none() {
+    let (policy, target_policy) = (group_leader(policy), group_leader(target_policy));
+    // Both ended up in the same group
+    let extra_policy = if policy == target_policy {
+        warn!("Ignoring relative bound of policy{policy} to its own group");
+        ExtraPolicy::None
+    } else if min.is_none() && max.is_none() {
         ExtraPolicy::None
     } else {
         ExtraPolicy::RelRangeBound(RelRangeBound {
@@ -99,6 +107,7 @@ pub fn set_policy_freq_offset(_: i32, _: isize) {
 }
 
 pub fn set_ignore_policy(policy: i32, val: bool) {
+    let policy = group_leader(policy);
     IGNORE_MAP
         .get()
         .unwrap()
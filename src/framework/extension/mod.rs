@@ -49,3 +49,12 @@ impl Extension {
         let _ = self.sx.try_send(trigger.into_box());
     }
 }
+
+#[cfg(test)]
+impl Extension {
+    // Triggers go nowhere, for tests of the looper side
+    pub fn detached() -> Self {
+        let (sx, _) = mpsc::sync_channel(1);
+        Self { sx }
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    fmt::Write as _,
+    fs, iter,
+    time::{Duration, Instant},
+};
+
+use super::Node;
+use crate::framework::error::Result;
+
+const CUR_FREQ_INTERVAL: Duration = Duration::from_millis(250);
+
+impl Node {
+    // Throttled, this is called every frame
+    pub fn set_cur_freq<'a>(
+        &mut self,
+        freqs: impl Iterator<Item = (i32, isize)>,
+        write_errors: impl Iterator<Item = (i32, &'a str)>,
+    ) -> Result<()> {
+        if self.cur_freq_timer.elapsed() < CUR_FREQ_INTERVAL {
+            return Ok(());
+        }
+
+        self.cur_freq_timer = Instant::now();
+        let write_errors: Vec<_> = write_errors.collect();
+        self.write_cur_freq(freqs, &write_errors)
+    }
+
+    pub fn clear_cur_freq(&self) -> Result<()> {
+        self.write_cur_freq(iter::empty(), &[])
+    }
+
+    fn write_cur_freq(
+        &self,
+        freqs: impl Iterator<Item = (i32, isize)>,
+        write_errors: &[(i32, &str)],
+    ) -> Result<()> {
+        let mut content = String::new();
+        for (policy, freq) in freqs {
+            match write_errors.iter().find(|(p, _)| *p == policy) {
+                Some((_, error)) => {
+                    let _ = writeln!(content, "policy{policy}=write failed ({error})");
+                }
+                None => {
+                    let _ = writeln!(content, "policy{policy}={freq}");
+                }
+            }
+        }
+
+        fs::write(self.path.join("cur_freq"), content)?;
+        Ok(())
+    }
+}
@@ -1,52 +1,139 @@
+mod cur_freq;
 mod power_mode;
+mod target_fps;
 
 use std::{
     collections::HashMap,
-    fs,
-    path::Path,
+    fs::{self, set_permissions},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
     time::{Duration, Instant},
 };
 
 use crate::framework::error::{Error, Result};
 use likely_stable::unlikely;
+use log::{info, warn};
 pub use power_mode::Mode;
-
-const NODE_PATH: &str = "/dev/fas_rs";
+use sys_mount::Mount;
+
+const NODE_PATHS: [&str; 3] = [
+    "/dev/fas_rs",
+    "/data/adb/fas-rs/nodes",
+    "/data/adb/fas-rs/tmpfs",
+];
+const TMPFS_NODE_PATH: &str = "/data/adb/fas-rs/tmpfs";
+const NODE_PATH_RECORD: &str = "/sdcard/Android/fas-rs/node_path";
+const NODE_CONTEXT: &str = "u:object_r:system_file:s0";
 const REFRESH_TIME: Duration = Duration::from_secs(1);
+// Bump on any breaking change to the node formats
+const PROTOCOL_VERSION: u32 = 1;
 
 pub struct Node {
+    path: PathBuf,
     map: HashMap<String, String>,
     timer: Instant,
+    cur_freq_timer: Instant,
+    target_fps: String,
+    path_recorded: bool,
 }
 
 impl Node {
     pub fn init() -> Result<Self> {
-        let _ = fs::create_dir(NODE_PATH);
+        let path = NODE_PATHS
+            .iter()
+            .map(Path::new)
+            .find(|path| {
+                Self::prepare_dir(path)
+                    .inspect_err(|e| warn!("Node directory {path:?} is unusable: {e}"))
+                    .is_ok()
+            })
+            .ok_or(Error::Other("No usable node directory"))?
+            .to_path_buf();
+
+        info!("Node directory: {path:?}");
 
         let mut result = Self {
+            path,
             map: HashMap::new(),
             timer: Instant::now(),
+            cur_freq_timer: Instant::now(),
+            target_fps: String::new(),
+            path_recorded: false,
         };
 
+        if let Err(e) = result.record_path() {
+            warn!("Failed to write {NODE_PATH_RECORD}: {e}, retrying once storage is available");
+        }
+
         let _ = result.remove_node("mode");
         result.create_node("mode", "balance")?;
 
+        let _ = result.remove_node("version");
+        result.create_node("version", &version_node())?;
+
+        let _ = result.remove_node("cur_freq");
+        result.create_node("cur_freq", "")?;
+
+        let _ = result.remove_node("target_fps");
+        result.create_node("target_fps", "")?;
+
         Ok(result)
     }
 
+    fn prepare_dir(path: &Path) -> Result<()> {
+        fs::create_dir_all(path)?;
+
+        if path == Path::new(TMPFS_NODE_PATH) && !is_mountpoint(path) {
+            mount_tmpfs(path)?;
+        }
+
+        set_permissions(path, PermissionsExt::from_mode(0o755))?;
+        let _ = Command::new("chcon").arg(NODE_CONTEXT).arg(path).status();
+
+        let probe = path.join(".probe");
+        fs::write(&probe, "fas-rs")?;
+        let content = fs::read_to_string(&probe)?;
+        let _ = fs::remove_file(&probe);
+
+        if content == "fas-rs" {
+            Ok(())
+        } else {
+            Err(Error::Other("Node round-trip check failed"))
+        }
+    }
+
+    // /sdcard may not be mounted yet at boot
+    fn record_path(&mut self) -> Result<()> {
+        fs::write(NODE_PATH_RECORD, self.path.display().to_string())?;
+        self.path_recorded = true;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     pub fn create_node<S: AsRef<str>>(&mut self, i: S, d: S) -> Result<()> {
         let id = i.as_ref();
         let default = d.as_ref();
 
-        let path = Path::new(NODE_PATH).join(id);
-        fs::write(path, default)?;
+        let path = self.path.join(id);
+        let created = !path.exists();
+        fs::write(&path, default)?;
+
+        // Only new files need labeling
+        if created {
+            set_permissions(&path, PermissionsExt::from_mode(0o666))?;
+            let _ = Command::new("chcon").arg(NODE_CONTEXT).arg(&path).status();
+        }
         self.refresh()
     }
 
     pub fn remove_node<S: AsRef<str>>(&mut self, i: S) -> Result<()> {
         let id = i.as_ref();
 
-        let path = Path::new(NODE_PATH).join(id);
+        let path = self.path.join(id);
         fs::remove_file(path)?;
 
         self.refresh()
@@ -56,6 +143,10 @@ impl Node {
         let id = id.as_ref();
 
         if unlikely(self.timer.elapsed() > REFRESH_TIME) {
+            self.timer = Instant::now();
+            if unlikely(!self.path_recorded) && self.record_path().is_ok() {
+                info!("Node directory recorded in {NODE_PATH_RECORD}");
+            }
             self.refresh()?;
         }
 
@@ -65,7 +156,7 @@ impl Node {
     }
 
     fn refresh(&mut self) -> Result<()> {
-        for entry in fs::read_dir(NODE_PATH)? {
+        for entry in fs::read_dir(&self.path)? {
             let Ok(entry) = entry else {
                 continue;
             };
@@ -80,3 +171,40 @@ impl Node {
         Ok(())
     }
 }
+
+fn is_mountpoint(path: &Path) -> bool {
+    fs::read_to_string("/proc/mounts").is_ok_and(|mounts| {
+        mounts
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .any(|mountpoint| Path::new(mountpoint) == path)
+    })
+}
+
+fn version_node() -> String {
+    format!(
+        "version={}\nprotocol={PROTOCOL_VERSION}\n",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+fn mount_tmpfs(path: &Path) -> Result<()> {
+    Mount::builder()
+        .fstype("tmpfs")
+        .data("mode=0755")
+        .mount("tmpfs", path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::version_node;
+
+    #[test]
+    fn version_node_format_is_pinned() {
+        assert_eq!(
+            version_node(),
+            format!("version={}\nprotocol=1\n", env!("CARGO_PKG_VERSION"))
+        );
+    }
+}
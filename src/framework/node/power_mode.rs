@@ -47,4 +47,8 @@ impl Node {
 
         Mode::from_str(mode.trim())
     }
+
+    pub fn set_mode(&mut self, mode: Mode) -> Result<()> {
+        self.create_node("mode", &mode.to_string())
+    }
 }
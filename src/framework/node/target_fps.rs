@@ -0,0 +1,29 @@
+use std::fs;
+
+use super::Node;
+use crate::framework::error::Result;
+
+impl Node {
+    // Only written when it changes, this is called for every frame
+    pub fn set_target_fps(&mut self, target_fps: Option<u32>, regime: &str) -> Result<()> {
+        let content = format!(
+            "target_fps={}\nregime={regime}\n",
+            target_fps.unwrap_or_default()
+        );
+        self.write_target_fps(content)
+    }
+
+    pub fn clear_target_fps(&mut self) -> Result<()> {
+        self.write_target_fps(String::new())
+    }
+
+    fn write_target_fps(&mut self, content: String) -> Result<()> {
+        if content == self.target_fps {
+            return Ok(());
+        }
+
+        fs::write(self.path.join("target_fps"), &content)?;
+        self.target_fps = content;
+        Ok(())
+    }
+}
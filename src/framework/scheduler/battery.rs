@@ -0,0 +1,93 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+use log::info;
+
+use super::capabilities::{Availability, Feature};
+use crate::Config;
+
+const CAPACITY_NODE: &str = "/sys/class/power_supply/battery/capacity";
+const REFRESH_TIME: Duration = Duration::from_secs(30);
+
+pub struct Battery {
+    capacity: Option<u8>,
+    timer: Instant,
+    last_cap: u8,
+}
+
+impl Battery {
+    pub fn new() -> Self {
+        Self {
+            capacity: read_capacity(),
+            timer: Instant::now(),
+            last_cap: 100,
+        }
+    }
+
+    // Max frequency percentage allowed at the current battery level
+    // Linear from `battery_cap_thresh` down to `battery_cap_floor`
+    pub fn max_freq_per(&mut self, config: &mut Config) -> u8 {
+        let thresh = config.config().battery_cap_thresh.min(100);
+        let floor = config.config().battery_cap_floor.min(100);
+
+        if thresh == 0 {
+            return 100;
+        }
+
+        if self.timer.elapsed() >= REFRESH_TIME {
+            self.timer = Instant::now();
+            self.capacity = read_capacity();
+        }
+
+        let Some(capacity) = self.capacity else {
+            return 100;
+        };
+
+        let cap = if capacity >= thresh {
+            100
+        } else {
+            let rolloff = u32::from(100 - floor) * u32::from(capacity) / u32::from(thresh);
+            floor + u8::try_from(rolloff).unwrap_or_default()
+        };
+
+        if cap != self.last_cap {
+            info!("Battery at {capacity}%, max_freq_per capped at {cap}%");
+            self.last_cap = cap;
+        }
+
+        cap
+    }
+}
+
+impl Feature for Battery {
+    fn availability(&self) -> Availability {
+        self.capacity
+            .map(|_| ())
+            .ok_or("off (battery capacity not readable)")
+    }
+}
+
+fn read_capacity() -> Option<u8> {
+    fs::read_to_string(CAPACITY_NODE)
+        .ok()
+        .and_then(|capacity| capacity.trim().parse().ok())
+}
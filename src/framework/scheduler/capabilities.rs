@@ -0,0 +1,76 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use log::info;
+
+// Err carries why the feature can't work on this device
+pub type Availability = Result<(), &'static str>;
+
+// Optional modules
+pub trait Feature {
+    fn availability(&self) -> Availability;
+}
+
+struct Capability {
+    name: &'static str,
+    configured: bool,
+    availability: Availability,
+}
+
+#[derive(Default)]
+pub struct Capabilities {
+    capabilities: Vec<Capability>,
+}
+
+impl Capabilities {
+    pub fn register(&mut self, name: &'static str, configured: bool, feature: &dyn Feature) {
+        self.capabilities.push(Capability {
+            name,
+            configured,
+            availability: feature.availability(),
+        });
+    }
+
+    pub fn report(&self) {
+        info!("Capabilities:");
+        for capability in &self.capabilities {
+            info!("  {:<20} {}", capability.name, capability.state());
+        }
+    }
+}
+
+impl Capability {
+    const fn state(&self) -> &'static str {
+        match self.availability {
+            _ if !self.configured => "off (disabled in config)",
+            Ok(()) => "on",
+            Err(reason) => reason,
+        }
+    }
+}
+
+// The capabilities node format, one name=state line per feature
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for capability in &self.capabilities {
+            writeln!(f, "{}={}", capability.name, capability.state())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use log::info;
+
+use super::capabilities::{Availability, Feature};
+
+const COOLING_DEVICES: &str = "/sys/class/thermal";
+const CPUFREQ_POLICIES: &str = "/sys/devices/system/cpu/cpufreq";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+struct Episode {
+    start: Instant,
+    max_state: u64,
+}
+
+#[derive(Debug)]
+struct CoolingDevice {
+    cdev_type: String,
+    // First cpu of the cpufreq policy this device throttles
+    cpu: Option<usize>,
+    state_path: PathBuf,
+    state: u64,
+    episode: Option<Episode>,
+}
+
+// cpufreq/devfreq cooling device states
+pub struct CoolingDevices {
+    devices: Vec<CoolingDevice>,
+    timer: Instant,
+}
+
+impl CoolingDevices {
+    pub fn new() -> Self {
+        Self::with_paths(
+            Path::new(COOLING_DEVICES),
+            &policy_cpus(Path::new(CPUFREQ_POLICIES)),
+        )
+    }
+
+    fn with_paths(cooling_devices: &Path, policies: &[usize]) -> Self {
+        let devices = fs::read_dir(cooling_devices)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let name = path.file_name()?.to_str()?;
+                if !name.starts_with("cooling_device") {
+                    return None;
+                }
+
+                let cdev_type = fs::read_to_string(path.join("type")).ok()?;
+                let cdev_type = cdev_type.trim().to_string();
+                if !cdev_type.contains("cpufreq") && !cdev_type.contains("devfreq") {
+                    return None;
+                }
+
+                Some(CoolingDevice {
+                    cpu: parse_cpu(&cdev_type, policies),
+                    cdev_type,
+                    state_path: path.join("cur_state"),
+                    state: 0,
+                    episode: None,
+                })
+            })
+            .collect();
+
+        Self {
+            devices,
+            timer: Instant::now(),
+        }
+    }
+
+    // Returns whether the states were polled this time
+    pub fn update(&mut self) -> bool {
+        if self.timer.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.timer = Instant::now();
+        self.poll();
+        true
+    }
+
+    fn poll(&mut self) {
+        for device in &mut self.devices {
+            let Some(state) = fs::read_to_string(&device.state_path)
+                .ok()
+                .and_then(|state| state.trim().parse().ok())
+            else {
+                continue;
+            };
+            device.state = state;
+
+            match device.episode.as_mut() {
+                None if state > 0 => {
+                    info!("Kernel throttling started on {}", device.cdev_type);
+                    device.episode = Some(Episode {
+                        start: Instant::now(),
+                        max_state: state,
+                    });
+                }
+                Some(episode) if state > 0 => episode.max_state = episode.max_state.max(state),
+                Some(episode) => {
+                    info!(
+                        "Kernel throttling ended on {}, lasted {:?}, max state {}",
+                        device.cdev_type,
+                        episode.start.elapsed(),
+                        episode.max_state
+                    );
+                    device.episode = None;
+                }
+                None => (),
+            }
+        }
+    }
+
+    // By first cpu of the policy, 0 means not throttling
+    pub fn cpufreq_states(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.devices
+            .iter()
+            .filter_map(|device| Some((device.cpu?, device.state)))
+    }
+}
+
+impl Feature for CoolingDevices {
+    fn availability(&self) -> Availability {
+        if self.devices.is_empty() {
+            Err("off (no cpufreq/devfreq cooling device)")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// First cpus of all cpufreq policies in order
+fn policy_cpus(cpufreq_policies: &Path) -> Vec<usize> {
+    let mut cpus: Vec<usize> = fs::read_dir(cpufreq_policies)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .ok()?
+                .file_name()
+                .to_str()?
+                .strip_prefix("policy")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    cpus.sort_unstable();
+    cpus
+}
+
+// `cpufreq-cpuN` names a cpu, `thermal-cpufreq-N` a policy index
+fn parse_cpu(cdev_type: &str, policies: &[usize]) -> Option<usize> {
+    cdev_type
+        .strip_prefix("cpufreq-cpu")
+        .and_then(|cpu| cpu.parse().ok())
+        .or_else(|| {
+            let index: usize = cdev_type.strip_prefix("thermal-cpufreq-")?.parse().ok()?;
+            policies.get(index).copied()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::{CoolingDevices, parse_cpu, policy_cpus};
+    use crate::test_utils::{fixture_dir, write_files};
+
+    fn add_device(root: &Path, index: usize, cdev_type: &str, state: u64) {
+        let path = root.join(format!("cooling_device{index}"));
+        fs::create_dir_all(&path).unwrap();
+        write_files(
+            &path,
+            &[
+                ("type", &format!("{cdev_type}\n")),
+                ("cur_state", &format!("{state}\n")),
+            ],
+        );
+    }
+
+    fn set_state(root: &Path, index: usize, state: u64) {
+        fs::write(
+            root.join(format!("cooling_device{index}/cur_state")),
+            format!("{state}\n"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn cdev_types_map_to_policies() {
+        let policies = [0, 4, 7];
+
+        assert_eq!(parse_cpu("thermal-cpufreq-0", &policies), Some(0));
+        assert_eq!(parse_cpu("thermal-cpufreq-2", &policies), Some(7));
+        assert_eq!(parse_cpu("cpufreq-cpu4", &policies), Some(4));
+        assert_eq!(parse_cpu("thermal-cpufreq-3", &policies), None);
+        assert_eq!(parse_cpu("thermal-devfreq-0", &policies), None);
+        assert_eq!(parse_cpu("cpufreq-cpux", &policies), None);
+    }
+
+    #[test]
+    fn policy_dirs_are_sorted_by_cpu() {
+        let root = fixture_dir("cooling/policies");
+        for name in ["policy7", "policy0", "policy4", "cpuidle", "policyX"] {
+            fs::create_dir_all(root.join(name)).unwrap();
+        }
+
+        assert_eq!(policy_cpus(&root), [0, 4, 7]);
+    }
+
+    #[test]
+    fn only_cpufreq_and_devfreq_devices_are_tracked() {
+        let root = fixture_dir("cooling/types");
+        add_device(&root, 0, "thermal-cpufreq-0", 0);
+        add_device(&root, 1, "thermal-cpufreq-1", 2);
+        add_device(&root, 2, "gpu-devfreq", 1);
+        add_device(&root, 3, "battery", 5);
+        fs::create_dir_all(root.join("thermal_zone0")).unwrap();
+
+        let mut cooling = CoolingDevices::with_paths(&root, &[0, 4]);
+        cooling.poll();
+
+        assert_eq!(cooling.devices.len(), 3);
+        let mut states: Vec<_> = cooling.cpufreq_states().collect();
+        states.sort_unstable();
+        assert_eq!(states, [(0, 0), (4, 2)]);
+    }
+
+    #[test]
+    fn throttle_episodes_are_tracked() {
+        let root = fixture_dir("cooling/episodes");
+        add_device(&root, 0, "thermal-cpufreq-0", 0);
+        let mut cooling = CoolingDevices::with_paths(&root, &[0]);
+
+        cooling.poll();
+        assert!(cooling.devices[0].episode.is_none());
+
+        for state in [2, 5, 3] {
+            set_state(&root, 0, state);
+            cooling.poll();
+        }
+        let episode = cooling.devices[0].episode.as_ref().unwrap();
+        assert_eq!(episode.max_state, 5);
+        assert_eq!(cooling.devices[0].state, 3);
+
+        set_state(&root, 0, 0);
+        cooling.poll();
+        assert!(cooling.devices[0].episode.is_none());
+        assert_eq!(cooling.cpufreq_states().collect::<Vec<_>>(), [(0, 0)]);
+    }
+
+    #[test]
+    fn unreadable_state_keeps_the_last_one() {
+        let root = fixture_dir("cooling/unreadable");
+        add_device(&root, 0, "thermal-cpufreq-0", 4);
+        let mut cooling = CoolingDevices::with_paths(&root, &[0]);
+        cooling.poll();
+
+        fs::write(root.join("cooling_device0/cur_state"), "busy\n").unwrap();
+        cooling.poll();
+
+        assert_eq!(cooling.devices[0].state, 4);
+        assert!(cooling.devices[0].episode.is_some());
+    }
+}
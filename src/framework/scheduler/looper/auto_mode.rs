@@ -0,0 +1,168 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    fs::OpenOptions,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use log::warn;
+
+use crate::framework::{
+    node::Mode,
+    scheduler::capabilities::{Availability, Feature},
+};
+
+// Hysteresis bands over highest fas frequency / max frequency
+const POWERSAVE_TO_BALANCE: f64 = 0.6;
+const BALANCE_TO_PERFORMANCE: f64 = 0.85;
+const PERFORMANCE_TO_BALANCE: f64 = 0.7;
+const BALANCE_TO_POWERSAVE: f64 = 0.4;
+
+pub struct AutoMode {
+    enabled: bool,
+    availability: Availability,
+    dwell: Duration,
+    candidate: Option<(Mode, Instant)>,
+}
+
+impl AutoMode {
+    pub fn new(enabled: bool, dwell: Duration, max_freq: isize, mode_node: &Path) -> Self {
+        let availability = Self::probe(max_freq, mode_node);
+        if let (true, Err(reason)) = (enabled, availability) {
+            warn!("auto_mode is {reason}, the mode is only switched manually");
+        }
+
+        Self {
+            enabled,
+            availability,
+            dwell,
+            candidate: None,
+        }
+    }
+
+    fn probe(max_freq: isize, mode_node: &Path) -> Availability {
+        if max_freq <= 0 {
+            return Err("off (no cpu frequency table to compute the load)");
+        }
+
+        OpenOptions::new()
+            .write(true)
+            .open(mode_node)
+            .map(|_| ())
+            .map_err(|_| "off (mode node not writable)")
+    }
+
+    // Returns the mode to switch to once the dwell time passed
+    pub fn update(&mut self, mode: Mode, load: f64) -> Option<Mode> {
+        if !self.enabled || self.availability.is_err() {
+            return None;
+        }
+
+        let Some(wanted) = Self::wanted_mode(mode, load) else {
+            self.candidate = None;
+            return None;
+        };
+
+        match self.candidate {
+            Some((candidate, since)) if candidate == wanted => {
+                if since.elapsed() >= self.dwell {
+                    self.candidate = None;
+                    Some(wanted)
+                } else {
+                    None
+                }
+            }
+            _ => {
+                self.candidate = Some((wanted, Instant::now()));
+                None
+            }
+        }
+    }
+
+    pub const fn reset(&mut self) {
+        self.candidate = None;
+    }
+
+    const fn wanted_mode(mode: Mode, load: f64) -> Option<Mode> {
+        match mode {
+            Mode::Powersave if load > POWERSAVE_TO_BALANCE => Some(Mode::Balance),
+            Mode::Balance if load > BALANCE_TO_PERFORMANCE => Some(Mode::Performance),
+            Mode::Balance if load < BALANCE_TO_POWERSAVE => Some(Mode::Powersave),
+            Mode::Performance if load < PERFORMANCE_TO_BALANCE => Some(Mode::Balance),
+            // fast is never picked automatically
+            _ => None,
+        }
+    }
+}
+
+impl Feature for AutoMode {
+    fn availability(&self) -> Availability {
+        self.availability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf, time::Duration};
+
+    use super::AutoMode;
+    use crate::{
+        framework::{node::Mode, scheduler::capabilities::Feature},
+        test_utils::fixture_dir,
+    };
+
+    fn mode_node(name: &str) -> PathBuf {
+        fixture_dir(&format!("auto-mode/{name}")).join("mode")
+    }
+
+    #[test]
+    fn probe_accepts_usable_inputs() {
+        let node = mode_node("usable");
+        fs::write(&node, "balance").unwrap();
+        let mut auto_mode = AutoMode::new(true, Duration::ZERO, 2_000_000, &node);
+
+        assert_eq!(auto_mode.availability(), Ok(()));
+        assert_eq!(auto_mode.update(Mode::Balance, 0.9), None);
+        assert_eq!(
+            auto_mode.update(Mode::Balance, 0.9),
+            Some(Mode::Performance)
+        );
+    }
+
+    #[test]
+    fn missing_mode_node_falls_back_to_manual() {
+        let node = mode_node("missing");
+        let mut auto_mode = AutoMode::new(true, Duration::ZERO, 2_000_000, &node);
+
+        assert!(auto_mode.availability().is_err());
+        assert_eq!(auto_mode.update(Mode::Balance, 0.9), None);
+        assert_eq!(auto_mode.update(Mode::Balance, 0.9), None);
+    }
+
+    #[test]
+    fn missing_frequency_table_falls_back_to_manual() {
+        let node = mode_node("no-table");
+        fs::write(&node, "balance").unwrap();
+        let mut auto_mode = AutoMode::new(true, Duration::ZERO, 0, &node);
+
+        assert!(auto_mode.availability().is_err());
+        assert_eq!(auto_mode.update(Mode::Balance, 0.9), None);
+        assert_eq!(auto_mode.update(Mode::Balance, 0.9), None);
+    }
+}
@@ -15,17 +15,26 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use likely_stable::unlikely;
 #[cfg(debug_assertions)]
 use log::debug;
+use log::info;
 
 use super::Buffer;
 use crate::{Extension, api::trigger_target_fps_change, framework::config::TargetFps};
 
+// How long target_fps has to stay undetected before the fallback kicks in
+const FALLBACK_DELAY: Duration = Duration::from_secs(5);
+
 impl Buffer {
     pub fn calculate_current_fps(&mut self) {
+        // Nothing to average right after a reset
+        if self.frametime_state.frametimes().is_empty() {
+            return;
+        }
+
         let avg_time_long = self.calculate_average_frametime(None);
         #[cfg(debug_assertions)]
         debug!("avg_time_long: {avg_time_long:?}");
@@ -53,27 +62,31 @@ impl Buffer {
     }
 
     fn calculate_average_frametime(&self, it_takes: Option<usize>) -> Duration {
-        let total_time: Duration = self
-            .frametime_state
-            .frametimes
-            .iter()
-            .take(it_takes.unwrap_or(self.frametime_state.frametimes.len()))
-            .sum::<Duration>()
-            .saturating_add(self.frametime_state.additional_frametime);
+        let len = self.frametime_state.frametimes.len();
+        let it_takes = it_takes.unwrap_or(len).min(len);
+
+        // Hot path, use the running sum
+        let total_time = if it_takes == len {
+            self.frametime_state.frametimes_sum
+        } else {
+            self.frametime_state
+                .frametimes
+                .iter()
+                .take(it_takes)
+                .sum::<Duration>()
+        }
+        .saturating_add(self.frametime_state.additional_frametime);
 
         total_time
-            .checked_div(
-                it_takes
-                    .unwrap_or(self.frametime_state.frametimes.len())
-                    .min(self.frametime_state.frametimes.len())
-                    .try_into()
-                    .unwrap(),
-            )
+            .checked_div(it_takes.try_into().unwrap())
             .unwrap_or_default()
     }
 
     pub fn calculate_target_fps(&mut self, extension: &Extension) {
         let new_target_fps = self.target_fps();
+        let new_target_fps = self
+            .fallback_target_fps(new_target_fps)
+            .map(|target_fps| target_fps.max(self.target_fps_state.min_target_fps));
         if self.target_fps_state.target_fps != new_target_fps || new_target_fps.is_none() {
             self.reset_frametime_state();
             if let Some(target_fps) = new_target_fps {
@@ -85,19 +98,61 @@ impl Buffer {
     }
 
     fn reset_frametime_state(&mut self) {
-        self.frametime_state.frametimes.clear();
+        self.frametime_state.clear();
     }
 
     fn trigger_target_fps_change(&self, extension: &Extension, target_fps: u32) {
         trigger_target_fps_change(extension, target_fps, self.package_info.pkg.clone());
     }
 
+    // For games that never settle on a target
+    fn fallback_target_fps(&mut self, detected: Option<u32>) -> Option<u32> {
+        self.target_fps_state.fallback_active = false;
+        if detected.is_some() {
+            self.target_fps_state.undetected_since = None;
+            return detected;
+        }
+
+        let fallback = self.target_fps_state.fallback_target_fps?;
+        let since = *self
+            .target_fps_state
+            .undetected_since
+            .get_or_insert_with(Instant::now);
+
+        if since.elapsed() < FALLBACK_DELAY {
+            return None;
+        }
+
+        if self.target_fps_state.target_fps != Some(fallback) {
+            info!(
+                "Target fps of [{}] not detected, fall back to {fallback}",
+                self.package_info.pkg
+            );
+        }
+
+        self.target_fps_state.fallback_active = true;
+        Some(fallback)
+    }
+
+    pub const fn target_regime(&self) -> &'static str {
+        match self.target_fps_state.target_fps {
+            None => "undetected",
+            Some(_) if self.target_fps_state.fallback_active => "fallback",
+            Some(_) => "detected",
+        }
+    }
+
     fn target_fps(&self) -> Option<u32> {
         let target_fpses = match &self.target_fps_state.target_fps_config {
             TargetFps::Value(t) => vec![*t],
             TargetFps::Array(arr) => arr.clone(),
         };
 
+        // Aim at the highest target until enough frames were seen
+        if self.target_fps_state.frames_seen < self.target_fps_state.min_frames {
+            return target_fpses.last().copied();
+        }
+
         let current_fps = self.frametime_state.current_fps_long;
 
         if unlikely(current_fps < (target_fpses.first()?.saturating_sub(10).max(10)).into()) {
@@ -118,3 +173,84 @@ impl Buffer {
         target_fpses.last().copied()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Buffer, FALLBACK_DELAY};
+    use crate::Extension;
+
+    const FRAME_60: Duration = Duration::from_micros(16_667);
+
+    #[test]
+    fn empty_buffer_keeps_last_fps() {
+        let mut buffer = Buffer::with_frametimes(Some(60), &[]);
+        buffer.frametime_state.current_fps_long = 60.0;
+        buffer.frametime_state.current_fps_short = 60.0;
+        buffer.calculate_current_fps();
+
+        assert!((buffer.frametime_state.current_fps_long - 60.0).abs() < f64::EPSILON);
+        assert!((buffer.frametime_state.current_fps_short - 60.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn zero_frametimes_are_ignored() {
+        let mut buffer = Buffer::with_frametimes(Some(60), &[FRAME_60; 120]);
+        buffer.calculate_current_fps();
+        let fps = buffer.frametime_state.current_fps_long;
+
+        buffer.push_frametime(Duration::ZERO, &Extension::detached());
+
+        assert_eq!(buffer.frametime_state.frametimes().len(), 120);
+        assert!((buffer.frametime_state.current_fps_long - fps).abs() < f64::EPSILON);
+        assert!(buffer.frametime_state.current_fps_long.is_finite());
+    }
+
+    #[test]
+    fn huge_frametimes_are_capped() {
+        let mut buffer = Buffer::with_frametimes(Some(60), &[FRAME_60; 120]);
+        let extension = Extension::detached();
+
+        buffer.push_frametime(Duration::MAX, &extension);
+        buffer.push_frametime(Duration::MAX, &extension);
+
+        let front = buffer.frametime_state.frametimes().front().copied();
+        assert_eq!(front, Some(Duration::from_secs(10)));
+        assert!(buffer.frametime_state.current_fps_long.is_finite());
+        assert!(buffer.frametime_state.current_fps_long > 0.0);
+    }
+
+    #[test]
+    fn average_includes_pending_frametime() {
+        let mut buffer = Buffer::with_frametimes(Some(60), &[FRAME_60; 60]);
+        buffer.frametime_state.additional_frametime = FRAME_60 * 60;
+        buffer.calculate_current_fps();
+
+        assert_eq!(buffer.frametime_state.avg_time_long, FRAME_60 * 2);
+    }
+
+    #[test]
+    fn zero_target_fps_is_raised_to_the_floor() {
+        let mut buffer = Buffer::with_frametimes(Some(0), &[FRAME_60; 120]);
+        buffer.calculate_current_fps();
+        buffer.calculate_target_fps(&Extension::detached());
+
+        assert_eq!(buffer.target_fps_state.target_fps, Some(10));
+    }
+
+    #[test]
+    fn target_regime_follows_the_fallback() {
+        let mut buffer = Buffer::with_frametimes(None, &[FRAME_60; 60]);
+        assert_eq!(buffer.target_regime(), "undetected");
+
+        buffer.target_fps_state.fallback_target_fps = Some(30);
+        buffer.target_fps_state.undetected_since = Instant::now().checked_sub(FALLBACK_DELAY);
+        buffer.target_fps_state.target_fps = buffer.fallback_target_fps(None);
+        assert_eq!(buffer.target_fps_state.target_fps, Some(30));
+        assert_eq!(buffer.target_regime(), "fallback");
+
+        buffer.target_fps_state.target_fps = buffer.fallback_target_fps(Some(60));
+        assert_eq!(buffer.target_regime(), "detected");
+    }
+}
@@ -24,8 +24,25 @@ use std::{
 
 use libc::pid_t;
 use likely_stable::unlikely;
+#[cfg(debug_assertions)]
+use log::debug;
 
-use crate::{Extension, framework::config::TargetFps};
+use crate::{
+    Extension,
+    framework::config::{ConfigConfig, TargetFps},
+};
+
+// Seconds of frames kept in the buffer
+const FRAMETIME_WINDOW: usize = 5;
+// Used to size the buffer before target_fps is matched
+const DEFAULT_TARGET_FPS: usize = 144;
+// Caps the buffer at `MAX_FRAMETIMES`
+const MAX_TARGET_FPS: usize = 288;
+const MAX_FRAMETIMES: usize = MAX_TARGET_FPS * FRAMETIME_WINDOW;
+// Fraction of the frame budget, shorter frames after a long one are queue flushes
+const SHORT_FRAME_RATIO: f64 = 0.2;
+// Longer frames are stalls
+const MAX_FRAMETIME: Duration = Duration::from_secs(10);
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum BufferWorkingState {
@@ -45,34 +62,94 @@ pub struct FrameTimeState {
     pub avg_time_long: Duration,
     pub current_fps_short: f64,
     pub avg_time_short: Duration,
-    pub frametimes: VecDeque<Duration>,
+    frametimes: VecDeque<Duration>,
+    raw_frametimes: VecDeque<Duration>,
     pub additional_frametime: Duration,
+    frametimes_sum: Duration,
+    merge_short_frames: bool,
 }
 
 impl FrameTimeState {
-    fn new() -> Self {
+    fn new(merge_short_frames: bool) -> Self {
         Self {
             current_fps_long: 0.0,
             avg_time_long: Duration::ZERO,
             current_fps_short: 0.0,
             avg_time_short: Duration::ZERO,
-            frametimes: VecDeque::with_capacity(1440),
+            frametimes: VecDeque::with_capacity(MAX_FRAMETIMES),
+            raw_frametimes: VecDeque::with_capacity(MAX_FRAMETIMES),
             additional_frametime: Duration::ZERO,
+            frametimes_sum: Duration::ZERO,
+            merge_short_frames,
         }
     }
+
+    // Read-only, keeps `frametimes_sum` in sync
+    pub const fn frametimes(&self) -> &VecDeque<Duration> {
+        &self.frametimes
+    }
+
+    // Before `merge_short_frames`, what the controller reacts to
+    pub const fn raw_frametimes(&self) -> &VecDeque<Duration> {
+        &self.raw_frametimes
+    }
+
+    #[cfg(test)]
+    fn push(&mut self, d: Duration) {
+        self.push_merged(d, d);
+    }
+
+    fn push_merged(&mut self, d: Duration, raw: Duration) {
+        self.frametimes.push_front(d);
+        self.raw_frametimes.push_front(raw);
+        self.frametimes_sum += d;
+    }
+
+    fn replace_front(&mut self, d: Duration) {
+        if let Some(front) = self.frametimes.front_mut() {
+            self.frametimes_sum -= *front;
+            self.frametimes_sum += d;
+            *front = d;
+        }
+    }
+
+    fn pop(&mut self) {
+        if let Some(d) = self.frametimes.pop_back() {
+            self.frametimes_sum -= d;
+        }
+        self.raw_frametimes.pop_back();
+    }
+
+    fn clear(&mut self) {
+        self.frametimes.clear();
+        self.raw_frametimes.clear();
+        self.frametimes_sum = Duration::ZERO;
+    }
 }
 
 #[derive(Debug)]
 pub struct TargetFpsState {
     pub target_fps: Option<u32>,
     target_fps_config: TargetFps,
+    fallback_target_fps: Option<u32>,
+    undetected_since: Option<Instant>,
+    fallback_active: bool,
+    min_frames: usize,
+    frames_seen: usize,
+    min_target_fps: u32,
 }
 
 impl TargetFpsState {
-    const fn new(target_fps_config: TargetFps) -> Self {
+    fn new(target_fps_config: TargetFps, config: &ConfigConfig) -> Self {
         Self {
             target_fps: None,
             target_fps_config,
+            fallback_target_fps: Some(config.fallback_target_fps).filter(|fps| *fps > 0),
+            undetected_since: None,
+            fallback_active: false,
+            min_frames: config.min_target_frames,
+            frames_seen: 0,
+            min_target_fps: config.min_target_fps.max(1),
         }
     }
 }
@@ -106,30 +183,76 @@ pub struct Buffer {
 }
 
 impl Buffer {
-    pub fn new(target_fps_config: TargetFps, pid: pid_t, pkg: String) -> Self {
+    pub fn new(
+        target_fps_config: TargetFps,
+        config: &ConfigConfig,
+        pid: pid_t,
+        pkg: String,
+    ) -> Self {
         Self {
             package_info: PackageInfo { pid, pkg },
-            frametime_state: FrameTimeState::new(),
-            target_fps_state: TargetFpsState::new(target_fps_config),
+            frametime_state: FrameTimeState::new(config.merge_short_frames),
+            target_fps_state: TargetFpsState::new(target_fps_config, config),
             state: BufferState::new(),
         }
     }
 
     pub fn push_frametime(&mut self, d: Duration, extension: &Extension) {
+        // A zero-length frame carries no timing information
+        if unlikely(d.is_zero()) {
+            return;
+        }
+        let d = d.min(MAX_FRAMETIME);
+
         self.frametime_state.additional_frametime = Duration::ZERO;
         self.state.last_update = Instant::now();
+        self.target_fps_state.frames_seen = self.target_fps_state.frames_seen.saturating_add(1);
 
-        while self.frametime_state.frametimes.len()
-            >= self.target_fps_state.target_fps.unwrap_or(144) as usize * 5
-        {
-            self.frametime_state.frametimes.pop_back();
+        let max_len = self
+            .target_fps_state
+            .target_fps
+            .map_or(DEFAULT_TARGET_FPS, |target_fps| target_fps as usize)
+            .min(MAX_TARGET_FPS)
+            * FRAMETIME_WINDOW;
+
+        while self.frametime_state.frametimes.len() >= max_len {
+            self.frametime_state.pop();
             self.try_usable();
         }
 
-        self.frametime_state.frametimes.push_front(d);
+        let merged = self.merge_short_frame(d);
+        self.frametime_state.push_merged(merged, d);
         self.try_calculate(extension);
     }
 
+    // Queued frames presented back-to-back after a long frame, only the stats see the merge
+    fn merge_short_frame(&mut self, d: Duration) -> Duration {
+        if !self.frametime_state.merge_short_frames {
+            return d;
+        }
+
+        let Some(budget) = self
+            .target_fps_state
+            .target_fps
+            .and_then(|target_fps| Duration::from_secs(1).checked_div(target_fps))
+        else {
+            return d;
+        };
+        let Some(last) = self.frametime_state.frametimes.front().copied() else {
+            return d;
+        };
+
+        if last > budget && d < budget.mul_f64(SHORT_FRAME_RATIO) {
+            let merged = (last + d) / 2;
+            #[cfg(debug_assertions)]
+            debug!("merged short frame: {last:?} + {d:?} -> 2 * {merged:?}");
+            self.frametime_state.replace_front(merged);
+            merged
+        } else {
+            d
+        }
+    }
+
     fn try_calculate(&mut self, extension: &Extension) {
         self.calculate_current_fps();
         if unlikely(self.state.calculate_timer.elapsed() >= Duration::from_millis(100)) {
@@ -156,3 +279,118 @@ impl Buffer {
         self.try_calculate(extension);
     }
 }
+
+#[cfg(test)]
+impl Buffer {
+    pub fn with_frametimes(target_fps: Option<u32>, frametimes: &[Duration]) -> Self {
+        let mut buffer = Self {
+            package_info: PackageInfo {
+                pid: 0,
+                pkg: "test".into(),
+            },
+            frametime_state: FrameTimeState::new(false),
+            target_fps_state: TargetFpsState {
+                target_fps,
+                target_fps_config: TargetFps::Value(target_fps.unwrap_or(60)),
+                fallback_target_fps: None,
+                undetected_since: None,
+                fallback_active: false,
+                min_frames: 0,
+                frames_seen: 0,
+                min_target_fps: 10,
+            },
+            state: BufferState::new(),
+        };
+
+        for frametime in frametimes {
+            buffer.frametime_state.push(*frametime);
+        }
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Buffer, FrameTimeState};
+    use crate::Extension;
+
+    fn naive_sum(state: &FrameTimeState) -> Duration {
+        state.frametimes().iter().sum()
+    }
+
+    // Deterministic frametimes in [min_us, max_us)
+    fn frametimes(count: usize, min_us: u64, max_us: u64) -> impl Iterator<Item = Duration> {
+        let mut seed = 0x2545_f491_u64;
+        (0..count).map(move |_| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            Duration::from_micros(min_us + (seed >> 33) % (max_us - min_us))
+        })
+    }
+
+    #[test]
+    fn running_sum_matches_naive_sum() {
+        let mut state = FrameTimeState::new(false);
+
+        for (i, frametime) in frametimes(2_000, 1_000, 50_000).enumerate() {
+            match i % 7 {
+                0 | 3 => state.pop(),
+                5 => state.replace_front(frametime),
+                _ => state.push(frametime),
+            }
+            assert_eq!(state.frametimes_sum, naive_sum(&state));
+        }
+
+        state.clear();
+        assert_eq!(state.frametimes_sum, Duration::ZERO);
+        assert!(state.frametimes().is_empty());
+
+        state.push(Duration::from_millis(16));
+        assert_eq!(state.frametimes_sum, Duration::from_millis(16));
+    }
+
+    #[test]
+    fn average_matches_recomputation() {
+        // Around 144fps, so the short window matches the configured target
+        let frames: Vec<_> = frametimes(720, 6_600, 7_200).collect();
+        let mut buffer = Buffer::with_frametimes(Some(144), &frames);
+        buffer.calculate_current_fps();
+
+        let count = u32::try_from(frames.len()).unwrap();
+        let long = frames.iter().sum::<Duration>() / count;
+        let short = buffer
+            .frametime_state
+            .frametimes()
+            .iter()
+            .take(144)
+            .sum::<Duration>()
+            / 144;
+
+        assert_eq!(buffer.frametime_state.avg_time_long, long);
+        assert_eq!(buffer.frametime_state.avg_time_short, short);
+    }
+
+    #[test]
+    fn merged_short_frame_keeps_the_raw_frametimes() {
+        let mut buffer = Buffer::with_frametimes(Some(60), &[Duration::from_millis(16); 60]);
+        buffer.frametime_state.merge_short_frames = true;
+        let extension = Extension::detached();
+
+        let long = Duration::from_millis(40);
+        let short = Duration::from_millis(2);
+        buffer.push_frametime(long, &extension);
+        buffer.push_frametime(short, &extension);
+
+        let state = &buffer.frametime_state;
+        assert_eq!(
+            state.frametimes().iter().take(2).sum::<Duration>(),
+            long + short
+        );
+        assert_eq!(state.frametimes()[0], state.frametimes()[1]);
+        assert_eq!(state.raw_frametimes()[0], short);
+        assert_eq!(state.raw_frametimes()[1], long);
+        assert_eq!(state.frametimes().len(), state.raw_frametimes().len());
+    }
+}
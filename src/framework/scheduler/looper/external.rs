@@ -0,0 +1,232 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    ffi::CString,
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    os::unix::ffi::OsStrExt,
+    path::Path,
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+use libc::mkfifo;
+use log::{error, info, warn};
+use parking_lot::Mutex;
+
+use crate::framework::{
+    error::{Error, Result},
+    scheduler::capabilities::{Availability, Feature},
+};
+
+const PIPE_NAME: &str = "frametime_pipe";
+const MAX_FRAMETIME: Duration = Duration::from_secs(1);
+
+// Frametimes pushed by another process through a named pipe
+// Package name first, then one timestamp in ns per line
+#[derive(Default)]
+pub struct ExternalFrames {
+    receiver: Option<Receiver<Duration>>,
+    connected: Arc<Mutex<Option<String>>>,
+}
+
+impl ExternalFrames {
+    pub fn new(enabled: bool, node_path: &Path) -> Self {
+        if !enabled {
+            return Self::default();
+        }
+
+        let path = node_path.join(PIPE_NAME);
+        if let Err(e) = create_pipe(&path) {
+            error!("Failed to create frametime pipe {path:?}: {e}");
+            return Self::default();
+        }
+
+        let (sx, rx) = mpsc::channel();
+        let connected = Arc::new(Mutex::new(None));
+
+        {
+            let connected = connected.clone();
+
+            if let Err(e) = thread::Builder::new()
+                .name("ExternalFrames".into())
+                .spawn(move || read_pipe(&path, &sx, &connected))
+            {
+                error!("Failed to start frametime pipe reader: {e}");
+                return Self::default();
+            }
+        }
+
+        Self {
+            receiver: Some(rx),
+            connected,
+        }
+    }
+
+    pub fn is_connected(&self, pkg: &str) -> bool {
+        self.connected.lock().as_deref() == Some(pkg)
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Duration> {
+        self.receiver.as_ref()?.recv_timeout(timeout).ok()
+    }
+
+    // Drop frames of the previous connection
+    pub fn drain(&self) {
+        if let Some(receiver) = &self.receiver {
+            while receiver.try_recv().is_ok() {}
+        }
+    }
+}
+
+impl Feature for ExternalFrames {
+    fn availability(&self) -> Availability {
+        self.receiver
+            .as_ref()
+            .map(|_| ())
+            .ok_or("off (failed to create the frametime pipe)")
+    }
+}
+
+fn create_pipe(path: &Path) -> Result<()> {
+    let _ = fs::remove_file(path);
+
+    let c_path =
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::Other("Illegal pipe path"))?;
+    if unsafe { mkfifo(c_path.as_ptr(), 0o666) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    Ok(())
+}
+
+fn read_pipe(path: &Path, sx: &Sender<Duration>, connected: &Mutex<Option<String>>) {
+    loop {
+        // Blocks until a writer opens the pipe
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Failed to open frametime pipe: {e}");
+                return;
+            }
+        };
+
+        let mut lines = BufReader::new(file).lines();
+        let Some(Ok(pkg)) = lines.next() else {
+            continue;
+        };
+        let pkg = pkg.trim().to_string();
+
+        info!("External frametime provider connected for [{pkg}]");
+        *connected.lock() = Some(pkg.clone());
+
+        let mut last_timestamp = None;
+        for line in lines {
+            let Ok(line) = line else {
+                break;
+            };
+
+            if parse_frame(&line, &mut last_timestamp)
+                .is_some_and(|frametime| sx.send(frametime).is_err())
+            {
+                return;
+            }
+        }
+
+        info!("External frametime provider disconnected for [{pkg}]");
+        *connected.lock() = None;
+    }
+}
+
+// Drops non-increasing timestamps, gaps over `MAX_FRAMETIME` resync
+fn parse_frame(line: &str, last_timestamp: &mut Option<u64>) -> Option<Duration> {
+    let Ok(timestamp) = line.trim().parse::<u64>() else {
+        warn!("Ignored malformed frame timestamp from external provider: {line:?}");
+        return None;
+    };
+
+    let frametime = match *last_timestamp {
+        Some(last) if timestamp <= last => {
+            warn!("Ignored non-monotonic frame timestamp from external provider");
+            return None;
+        }
+        Some(last) => Some(Duration::from_nanos(timestamp - last)),
+        None => None,
+    };
+
+    *last_timestamp = Some(timestamp);
+    frametime.filter(|frametime| *frametime <= MAX_FRAMETIME)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::parse_frame;
+
+    fn parse_all(lines: &[&str]) -> Vec<Duration> {
+        let mut last_timestamp = None;
+        lines
+            .iter()
+            .filter_map(|line| parse_frame(line, &mut last_timestamp))
+            .collect()
+    }
+
+    #[test]
+    fn parses_frame_intervals() {
+        let frames = parse_all(&["1000000000", "1016666666", " 1033333333 \r"]);
+        assert_eq!(
+            frames,
+            [
+                Duration::from_nanos(16_666_666),
+                Duration::from_nanos(16_666_667)
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_malformed_lines() {
+        let frames = parse_all(&[
+            "1000000000",
+            "",
+            "abc",
+            "-5",
+            "1.5e9",
+            "18446744073709551616",
+            "1016666666\0",
+            "1016666666",
+        ]);
+        assert_eq!(frames, [Duration::from_nanos(16_666_666)]);
+    }
+
+    #[test]
+    fn ignores_non_monotonic_timestamps() {
+        let frames = parse_all(&["2000000000", "2000000000", "1000000000", "2016666666"]);
+        assert_eq!(frames, [Duration::from_nanos(16_666_666)]);
+    }
+
+    #[test]
+    fn long_gap_resynchronizes() {
+        let frames = parse_all(&["0", "5000000000", "5016666666"]);
+        assert_eq!(frames, [Duration::from_nanos(16_666_666)]);
+    }
+}
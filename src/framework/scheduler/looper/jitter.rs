@@ -0,0 +1,103 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JitterStats {
+    pub mean_interval: Duration,
+    pub max_interval: Duration,
+    pub mean_jitter: Duration,
+}
+
+// Interval between control iterations vs the target frame period
+pub struct LoopJitter {
+    warn_ratio: f64,
+    last_iteration: Option<Instant>,
+    count: u32,
+    interval_sum: Duration,
+    interval_max: Duration,
+    jitter_sum: Duration,
+    timer: Instant,
+}
+
+impl LoopJitter {
+    pub fn new(warn_ratio: f64) -> Self {
+        Self {
+            warn_ratio,
+            last_iteration: None,
+            count: 0,
+            interval_sum: Duration::ZERO,
+            interval_max: Duration::ZERO,
+            jitter_sum: Duration::ZERO,
+            timer: Instant::now(),
+        }
+    }
+
+    // Returns the stats of the last window once every `REPORT_INTERVAL`
+    pub fn tick(&mut self, target_fps: u32) -> Option<JitterStats> {
+        let now = Instant::now();
+        let period = Duration::from_secs(1)
+            .checked_div(target_fps)
+            .unwrap_or_default();
+
+        if let Some(last) = self.last_iteration.replace(now) {
+            let interval = now - last;
+            self.count += 1;
+            self.interval_sum += interval;
+            self.interval_max = self.interval_max.max(interval);
+            self.jitter_sum += interval.abs_diff(period);
+        }
+
+        if self.timer.elapsed() < REPORT_INTERVAL || self.count == 0 {
+            return None;
+        }
+
+        let stats = JitterStats {
+            mean_interval: self.interval_sum / self.count,
+            max_interval: self.interval_max,
+            mean_jitter: self.jitter_sum / self.count,
+        };
+
+        if self.warn_ratio > 0.0 && stats.mean_jitter > period.mul_f64(self.warn_ratio) {
+            warn!(
+                "Control loop jitter is high: mean {:?}, max interval {:?}, target period {period:?}",
+                stats.mean_jitter, stats.max_interval
+            );
+        }
+
+        self.reset_window();
+        Some(stats)
+    }
+
+    pub fn reset(&mut self) {
+        self.last_iteration = None;
+        self.reset_window();
+    }
+
+    fn reset_window(&mut self) {
+        self.count = 0;
+        self.interval_sum = Duration::ZERO;
+        self.interval_max = Duration::ZERO;
+        self.jitter_sum = Duration::ZERO;
+        self.timer = Instant::now();
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write as _,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use libc::{PRIO_PROCESS, setpriority};
+use log::{error, info};
+use parking_lot::Mutex;
+
+use super::{buffer::Buffer, jitter::JitterStats};
+use crate::framework::{
+    error::Result,
+    scheduler::capabilities::{Availability, Feature},
+};
+
+const FRAMETIME_SAMPLES: usize = 600;
+const QUANTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+#[derive(Debug, Default)]
+struct MetricsData {
+    pkg: Option<String>,
+    target_fps: Option<u32>,
+    current_fps: f64,
+    control: isize,
+    thermal_fps_offset: f64,
+    cpu_freqs: BTreeMap<i32, isize>,
+    throttle_states: BTreeMap<usize, u64>,
+    jitter: JitterStats,
+    topapp_refresh_time: Duration,
+    frametimes: VecDeque<Duration>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    data: Option<Arc<Mutex<MetricsData>>>,
+}
+
+impl Metrics {
+    pub fn new(enabled: bool, addr: SocketAddr) -> Self {
+        if !enabled {
+            return Self::default();
+        }
+
+        let listener = match TcpListener::bind(addr) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind metrics server to {addr}: {e}");
+                return Self::default();
+            }
+        };
+        let data = Arc::new(Mutex::new(MetricsData::default()));
+
+        {
+            let data = data.clone();
+
+            if let Err(e) = thread::Builder::new()
+                .name("MetricsServer".into())
+                .spawn(move || serve(&listener, &data))
+            {
+                error!("Failed to start metrics server: {e}");
+                return Self::default();
+            }
+        }
+
+        info!("Metrics server started on {addr}");
+
+        Self { data: Some(data) }
+    }
+
+    pub fn record_frame(&self, frametime: Duration) {
+        let Some(mut data) = self.data.as_ref().and_then(|data| data.try_lock()) else {
+            return;
+        };
+
+        if data.frametimes.len() >= FRAMETIME_SAMPLES {
+            data.frametimes.pop_back();
+        }
+        data.frametimes.push_front(frametime);
+    }
+
+    pub fn update(
+        &self,
+        buffer: &Buffer,
+        control: isize,
+        thermal_fps_offset: f64,
+        cpu_freqs: impl Iterator<Item = (i32, isize)>,
+    ) {
+        let Some(mut data) = self.data.as_ref().and_then(|data| data.try_lock()) else {
+            return;
+        };
+
+        if data.pkg.as_ref() != Some(&buffer.package_info.pkg) {
+            data.pkg = Some(buffer.package_info.pkg.clone());
+            data.frametimes.clear();
+        }
+        data.target_fps = buffer.target_fps_state.target_fps;
+        data.current_fps = buffer.frametime_state.current_fps_short;
+        data.control = control;
+        data.thermal_fps_offset = thermal_fps_offset;
+        data.cpu_freqs = cpu_freqs.collect();
+    }
+
+    pub fn update_throttle_states(&self, states: impl Iterator<Item = (usize, u64)>) {
+        if let Some(mut data) = self.data.as_ref().and_then(|data| data.try_lock()) {
+            data.throttle_states = states.collect();
+        }
+    }
+
+    pub fn update_jitter(&self, jitter: JitterStats) {
+        if let Some(mut data) = self.data.as_ref().and_then(|data| data.try_lock()) {
+            data.jitter = jitter;
+        }
+    }
+
+    pub fn update_topapp_refresh_time(&self, refresh_time: Duration) {
+        if let Some(mut data) = self.data.as_ref().and_then(|data| data.try_lock()) {
+            data.topapp_refresh_time = refresh_time;
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Some(data) = self.data.as_ref() {
+            *data.lock() = MetricsData::default();
+        }
+    }
+}
+
+impl Feature for Metrics {
+    fn availability(&self) -> Availability {
+        self.data
+            .as_ref()
+            .map(|_| ())
+            .ok_or("off (metrics server failed to start)")
+    }
+}
+
+impl MetricsData {
+    fn render(&self) -> String {
+        let mut output = String::new();
+
+        let _ = writeln!(
+            output,
+            "# HELP fas_rs_cpu_freq_khz Frequency currently applied by fas-rs per cpufreq policy."
+        );
+        let _ = writeln!(output, "# TYPE fas_rs_cpu_freq_khz gauge");
+        for (policy, freq) in &self.cpu_freqs {
+            let _ = writeln!(output, "fas_rs_cpu_freq_khz{{policy=\"{policy}\"}} {freq}");
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP fas_rs_cpufreq_throttle_state Kernel cpufreq cooling state per policy, 0 means not throttled."
+        );
+        let _ = writeln!(output, "# TYPE fas_rs_cpufreq_throttle_state gauge");
+        for (policy, state) in &self.throttle_states {
+            let _ = writeln!(
+                output,
+                "fas_rs_cpufreq_throttle_state{{policy=\"{policy}\"}} {state}"
+            );
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP fas_rs_control_khz Last frequency adjustment computed by the controller."
+        );
+        let _ = writeln!(output, "# TYPE fas_rs_control_khz gauge");
+        let _ = writeln!(output, "fas_rs_control_khz {}", self.control);
+
+        let _ = writeln!(
+            output,
+            "# HELP fas_rs_thermal_fps_offset Target fps reduction applied by the built-in thermal control."
+        );
+        let _ = writeln!(output, "# TYPE fas_rs_thermal_fps_offset gauge");
+        let _ = writeln!(
+            output,
+            "fas_rs_thermal_fps_offset {}",
+            self.thermal_fps_offset
+        );
+
+        self.render_overhead(&mut output);
+
+        let Some(pkg) = &self.pkg else {
+            return output;
+        };
+
+        let _ = writeln!(output, "# HELP fas_rs_target_fps Matched target fps.");
+        let _ = writeln!(output, "# TYPE fas_rs_target_fps gauge");
+        let _ = writeln!(
+            output,
+            "fas_rs_target_fps{{pkg=\"{pkg}\"}} {}",
+            self.target_fps.unwrap_or_default()
+        );
+
+        let _ = writeln!(output, "# HELP fas_rs_fps Current fps.");
+        let _ = writeln!(output, "# TYPE fas_rs_fps gauge");
+        let _ = writeln!(output, "fas_rs_fps{{pkg=\"{pkg}\"}} {}", self.current_fps);
+
+        let mut frametimes: Vec<_> = self.frametimes.iter().copied().collect();
+        frametimes.sort_unstable();
+
+        let _ = writeln!(
+            output,
+            "# HELP fas_rs_frametime_seconds Frametime of the recent frames."
+        );
+        let _ = writeln!(output, "# TYPE fas_rs_frametime_seconds summary");
+        if let Some(max_index) = frametimes.len().checked_sub(1) {
+            for quantile in QUANTILES {
+                let index = (max_index as f64 * quantile).round() as usize;
+                let _ = writeln!(
+                    output,
+                    "fas_rs_frametime_seconds{{pkg=\"{pkg}\",quantile=\"{quantile}\"}} {}",
+                    frametimes[index].as_secs_f64()
+                );
+            }
+        }
+        let _ = writeln!(
+            output,
+            "fas_rs_frametime_seconds_sum{{pkg=\"{pkg}\"}} {}",
+            frametimes.iter().sum::<Duration>().as_secs_f64()
+        );
+        let _ = writeln!(
+            output,
+            "fas_rs_frametime_seconds_count{{pkg=\"{pkg}\"}} {}",
+            frametimes.len()
+        );
+
+        output
+    }
+
+    // Costs of fas-rs itself rather than of the game
+    fn render_overhead(&self, output: &mut String) {
+        let _ = writeln!(
+            output,
+            "# HELP fas_rs_loop_interval_seconds Wall time between control iterations."
+        );
+        let _ = writeln!(output, "# TYPE fas_rs_loop_interval_seconds gauge");
+        let _ = writeln!(
+            output,
+            "fas_rs_loop_interval_seconds{{stat=\"mean\"}} {}",
+            self.jitter.mean_interval.as_secs_f64()
+        );
+        let _ = writeln!(
+            output,
+            "fas_rs_loop_interval_seconds{{stat=\"max\"}} {}",
+            self.jitter.max_interval.as_secs_f64()
+        );
+
+        let _ = writeln!(
+            output,
+            "# HELP fas_rs_loop_jitter_seconds Mean deviation of the control interval from the target frame period."
+        );
+        let _ = writeln!(output, "# TYPE fas_rs_loop_jitter_seconds gauge");
+        let _ = writeln!(
+            output,
+            "fas_rs_loop_jitter_seconds {}",
+            self.jitter.mean_jitter.as_secs_f64()
+        );
+
+        let _ = writeln!(
+            output,
+            "# HELP fas_rs_topapp_refresh_seconds Interval of the top app poll, stretched while the same apps stay focused."
+        );
+        let _ = writeln!(output, "# TYPE fas_rs_topapp_refresh_seconds gauge");
+        let _ = writeln!(
+            output,
+            "fas_rs_topapp_refresh_seconds {}",
+            self.topapp_refresh_time.as_secs_f64()
+        );
+    }
+}
+
+fn serve(listener: &TcpListener, data: &Mutex<MetricsData>) {
+    // SAFETY: no pointers involved, on Linux who = 0 is the calling thread
+    if unsafe { setpriority(PRIO_PROCESS, 0, 19) } != 0 {
+        error!(
+            "Failed to lower metrics server priority: {}",
+            io::Error::last_os_error()
+        );
+    }
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+
+        let _ = respond(stream, data);
+    }
+}
+
+fn respond(mut stream: TcpStream, data: &Mutex<MetricsData>) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let _ = stream.read(&mut [0; 1024])?;
+
+    let body = data.lock().render();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+
+    Ok(())
+}
@@ -15,9 +15,14 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
+mod auto_mode;
 mod buffer;
 mod clean;
+mod external;
+mod jitter;
+mod metrics;
 mod policy;
+mod trace;
 
 use std::time::{Duration, Instant};
 
@@ -25,24 +30,32 @@ use frame_analyzer::Analyzer;
 use likely_stable::{likely, unlikely};
 #[cfg(debug_assertions)]
 use log::debug;
-use log::info;
+use log::{info, warn};
 use policy::{ControllerParams, controll::calculate_control};
 
-use super::{FasData, thermal::Thermal, topapp::TopAppsWatcher};
+use super::{
+    FasData, battery::Battery, capabilities::Capabilities, cooling::CoolingDevices,
+    thermal::Thermal, topapp::TopAppsWatcher,
+};
 use crate::{
     Controller,
     api::{trigger_load_fas, trigger_start_fas, trigger_stop_fas, trigger_unload_fas},
     framework::{
         Extension,
-        config::Config,
+        config::{Config, FreqPer},
         error::Result,
         node::{Mode, Node},
         pid_utils::get_process_name,
     },
 };
 
+use auto_mode::AutoMode;
 use buffer::{Buffer, BufferWorkingState};
 use clean::Cleaner;
+use external::ExternalFrames;
+use jitter::LoopJitter;
+use metrics::Metrics;
+use trace::TraceMarker;
 
 const DELAY_TIME: Duration = Duration::from_secs(3);
 
@@ -79,8 +92,16 @@ pub struct Looper {
     node: Node,
     extension: Extension,
     therminal: Thermal,
+    battery: Battery,
+    cooling: CoolingDevices,
     windows_watcher: TopAppsWatcher,
     cleaner: Cleaner,
+    metrics: Metrics,
+    trace_marker: TraceMarker,
+    loop_jitter: LoopJitter,
+    external_frames: ExternalFrames,
+    auto_mode: AutoMode,
+    applied_freq_per: Option<(Mode, u8, u64)>,
     fas_state: FasState,
     controller_state: ControllerState,
 }
@@ -88,12 +109,23 @@ pub struct Looper {
 impl Looper {
     pub fn new(
         analyzer: Analyzer,
-        config: Config,
+        mut config: Config,
         node: Node,
         extension: Extension,
         controller: Controller,
     ) -> Self {
-        Self {
+        let metrics = Metrics::new(config.config().metrics, config.config().metrics_addr);
+        let trace_marker = TraceMarker::new(config.config().trace_marker);
+        let loop_jitter = LoopJitter::new(config.config().loop_jitter_warn);
+        let external_frames = ExternalFrames::new(config.config().external_frametime, node.path());
+        let auto_mode = AutoMode::new(
+            config.config().auto_mode,
+            Duration::from_secs(config.config().auto_mode_dwell),
+            controller.max_freq(),
+            &node.path().join("mode"),
+        );
+
+        let mut looper = Self {
             analyzer_state: AnalyzerState {
                 analyzer,
                 restart_counter: 0,
@@ -103,8 +135,16 @@ impl Looper {
             node,
             extension,
             therminal: Thermal::new().unwrap(),
+            battery: Battery::new(),
+            cooling: CoolingDevices::new(),
             windows_watcher: TopAppsWatcher::new(),
             cleaner: Cleaner::new(),
+            metrics,
+            trace_marker,
+            loop_jitter,
+            external_frames,
+            auto_mode,
+            applied_freq_per: None,
             fas_state: FasState {
                 mode: Mode::Balance,
                 buffer: None,
@@ -117,6 +157,34 @@ impl Looper {
                 target_fps_offset: 0.0,
                 usage_sample_timer: Instant::now(),
             },
+        };
+
+        looper.report_capabilities();
+        looper
+    }
+
+    fn report_capabilities(&mut self) {
+        let config = self.config.config();
+        let mut capabilities = Capabilities::default();
+
+        capabilities.register("thermal", true, &self.therminal);
+        capabilities.register("cooling_devices", true, &self.cooling);
+        capabilities.register("battery_cap", config.battery_cap_thresh > 0, &self.battery);
+        capabilities.register("auto_mode", config.auto_mode, &self.auto_mode);
+        capabilities.register(
+            "external_frametime",
+            config.external_frametime,
+            &self.external_frames,
+        );
+        capabilities.register("trace_marker", config.trace_marker, &self.trace_marker);
+        capabilities.register("metrics", config.metrics, &self.metrics);
+
+        capabilities.report();
+        if let Err(e) = self
+            .node
+            .create_node("capabilities", &capabilities.to_string())
+        {
+            warn!("Failed to write the capabilities node: {e}");
         }
     }
 
@@ -133,6 +201,7 @@ impl Looper {
             if let Some(data) = self.recv_message() {
                 #[cfg(debug_assertions)]
                 debug!("original frametime: {:?}", data.frametime);
+                self.metrics.record_frame(data.frametime);
                 if let Some(state) = self.buffer_update(&data) {
                     match state {
                         BufferWorkingState::Usable => self.do_policy(),
@@ -164,6 +233,7 @@ impl Looper {
                     new_mode.to_string()
                 );
                 self.fas_state.mode = new_mode;
+                self.update_freq_per();
 
                 if self.fas_state.working_state == State::Working {
                     self.controller_state.controller.init_game(
@@ -175,13 +245,68 @@ impl Looper {
         }
     }
 
+    fn update_freq_per(&mut self) {
+        let battery_cap = self.battery.max_freq_per(&mut self.config);
+        let applied = (self.fas_state.mode, battery_cap, self.config.generation());
+        if self.applied_freq_per == Some(applied) {
+            return;
+        }
+        if self
+            .applied_freq_per
+            .is_some_and(|(_, _, generation)| generation != applied.2)
+        {
+            self.controller_state
+                .controller
+                .apply_config(&mut self.config);
+        }
+        self.applied_freq_per = Some(applied);
+
+        let mode_config = self.config.mode_config(self.fas_state.mode);
+        let max_freq_per = match &mode_config.max_freq_per {
+            FreqPer::BaseOnly(base) | FreqPer::Advanced { base, .. } => *base,
+        };
+        let controller = &mut self.controller_state.controller;
+
+        controller.set_freq_per(mode_config.min_freq_per, max_freq_per.min(battery_cap));
+        controller.set_policy_max_freq_per(
+            mode_config
+                .policy_max_freq_per
+                .iter()
+                .map(|&(policy, max_freq_per)| (policy, max_freq_per.min(battery_cap))),
+        );
+    }
+
     fn recv_message(&mut self) -> Option<FasData> {
+        if let Some(pid) = self.external_provider_pid() {
+            // The external provider takes priority
+            while self
+                .analyzer_state
+                .analyzer
+                .recv_timeout(Duration::ZERO)
+                .is_some()
+            {}
+
+            return self
+                .external_frames
+                .recv_timeout(Duration::from_millis(100))
+                .map(|frametime| FasData { pid, frametime });
+        }
+
+        self.external_frames.drain();
         self.analyzer_state
             .analyzer
             .recv_timeout(Duration::from_millis(100))
             .map(|(pid, frametime)| FasData { pid, frametime })
     }
 
+    fn external_provider_pid(&self) -> Option<i32> {
+        self.fas_state
+            .buffer
+            .as_ref()
+            .filter(|buffer| self.external_frames.is_connected(&buffer.package_info.pkg))
+            .map(|buffer| buffer.package_info.pid)
+    }
+
     fn update_analyzer(&mut self) -> Result<()> {
         for pid in self.windows_watcher.topapp_pids().iter().copied() {
             let pkg = get_process_name(pid)?;
@@ -212,28 +337,72 @@ impl Looper {
             return;
         }
 
-        let (control, is_janked) = if let Some(buffer) = &self.fas_state.buffer {
-            let target_fps_offset = self
-                .therminal
-                .target_fps_offset(&mut self.config, self.fas_state.mode);
-            calculate_control(
-                buffer,
-                &mut self.config,
-                self.fas_state.mode,
-                &mut self.controller_state,
-                target_fps_offset,
-            )
-            .unwrap_or_default()
-        } else {
+        self.update_freq_per();
+        if self.cooling.update() {
+            self.controller_state
+                .controller
+                .set_throttle_states(self.cooling.cpufreq_states());
+            self.metrics
+                .update_throttle_states(self.cooling.cpufreq_states());
+        }
+
+        let Some(buffer) = &self.fas_state.buffer else {
             return;
         };
 
+        let target_fps_offset = self
+            .therminal
+            .target_fps_offset(&mut self.config, self.fas_state.mode);
+        let (control, is_janked) = calculate_control(
+            buffer,
+            &mut self.config,
+            self.fas_state.mode,
+            &mut self.controller_state,
+            target_fps_offset,
+        )
+        .unwrap_or_default();
+
         #[cfg(debug_assertions)]
         debug!("control: {control}khz");
 
         self.controller_state
             .controller
             .fas_update_freq(control, is_janked);
+        self.metrics.update(
+            buffer,
+            control,
+            target_fps_offset,
+            self.controller_state.controller.cpu_freqs(),
+        );
+        if let Some(jitter) = buffer
+            .target_fps_state
+            .target_fps
+            .and_then(|target_fps| self.loop_jitter.tick(target_fps))
+        {
+            self.metrics.update_jitter(jitter);
+        }
+        self.metrics
+            .update_topapp_refresh_time(self.windows_watcher.refresh_time());
+        self.trace_marker.update(
+            control,
+            is_janked,
+            target_fps_offset,
+            self.controller_state.controller.cpu_freqs(),
+        );
+        let _ = self.node.set_cur_freq(
+            self.controller_state.controller.cpu_freqs(),
+            self.controller_state.controller.write_errors(),
+        );
+
+        let load = self.controller_state.controller.freq_load();
+        if let Some(new_mode) = self.auto_mode.update(self.fas_state.mode, load) {
+            info!(
+                "Auto mode: {} -> {}, freq load: {load:.2}",
+                self.fas_state.mode.to_string(),
+                new_mode.to_string()
+            );
+            let _ = self.node.set_mode(new_mode);
+        }
     }
 
     pub fn retain_topapp(&mut self) {
@@ -250,6 +419,7 @@ impl Looper {
                 let pkg = buffer.package_info.pkg.clone();
                 trigger_unload_fas(&self.extension, buffer.package_info.pid, pkg);
                 self.fas_state.buffer = None;
+                let _ = self.node.clear_target_fps();
             }
         }
 
@@ -265,6 +435,11 @@ impl Looper {
             State::Working => {
                 self.fas_state.working_state = State::NotWorking;
                 self.cleaner.undo_cleanup();
+                self.metrics.clear();
+                self.auto_mode.reset();
+                self.loop_jitter.reset();
+                self.windows_watcher.restore_cadence();
+                let _ = self.node.clear_cur_freq();
                 self.controller_state
                     .controller
                     .init_default(&self.extension);
@@ -309,6 +484,9 @@ impl Looper {
 
         if let Some(buffer) = self.fas_state.buffer.as_mut() {
             buffer.push_frametime(frametime, &self.extension);
+            let _ = self
+                .node
+                .set_target_fps(buffer.target_fps_state.target_fps, buffer.target_regime());
             Some(buffer.state.working_state)
         } else {
             let Ok(pkg) = get_process_name(data.pid) else {
@@ -320,7 +498,7 @@ impl Looper {
 
             trigger_load_fas(&self.extension, pid, pkg.clone());
 
-            let mut buffer = Buffer::new(target_fps, pid, pkg);
+            let mut buffer = Buffer::new(target_fps, &self.config.config(), pid, pkg);
             buffer.push_frametime(frametime, &self.extension);
 
             self.fas_state.buffer = Some(buffer);
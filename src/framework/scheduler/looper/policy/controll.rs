@@ -15,15 +15,21 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use std::time::{Duration, Instant};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
 
 use likely_stable::unlikely;
 #[cfg(debug_assertions)]
 use log::debug;
+use log::error;
 
 use super::super::buffer::Buffer;
 use crate::framework::{config::MarginFps, prelude::*, scheduler::looper::ControllerState};
 
+static NEGATIVE_MARGIN_REPORTED: AtomicBool = AtomicBool::new(false);
+
 pub fn calculate_control(
     buffer: &Buffer,
     config: &mut Config,
@@ -32,7 +38,7 @@ pub fn calculate_control(
     target_fps_offset_thermal: f64,
 ) -> Option<(isize, bool)> // control, is_janked
 {
-    if unlikely(buffer.frametime_state.frametimes.len() < 60) {
+    if unlikely(buffer.frametime_state.frametimes().len() < 60) {
         return None;
     }
 
@@ -45,10 +51,27 @@ pub fn calculate_control(
             .map_or_else(|| target_fps / 60.0 * f64::from(*base), f64::from),
     };
 
-    assert!(margin_fps.is_sign_positive(), "margin_fps must be positive");
+    debug_assert!(margin_fps.is_sign_positive(), "margin_fps must be positive");
+    let margin_fps = if margin_fps.is_sign_positive() {
+        margin_fps
+    } else {
+        if !NEGATIVE_MARGIN_REPORTED.swap(true, Ordering::Relaxed) {
+            error!("margin_fps must be positive, got {margin_fps:.2}, using 0 instead");
+        }
+        0.0
+    };
 
-    let target_fps = (target_fps + target_fps_offset_thermal).clamp(0.0, target_fps);
-    let adjusted_target_fps = adjust_target_fps(target_fps, controller_state) - margin_fps;
+    let min_target_fps = config.config().min_target_fps;
+    let target_fps = clamp_target_fps(
+        target_fps + target_fps_offset_thermal,
+        target_fps,
+        min_target_fps,
+    );
+    let adjusted_target_fps = clamp_target_fps(
+        adjust_target_fps(target_fps, controller_state) - margin_fps,
+        target_fps,
+        min_target_fps,
+    );
     let adjusted_last_frame = get_normalized_last_frame(buffer, adjusted_target_fps);
     let target_frametime = Duration::from_secs(1);
 
@@ -65,10 +88,16 @@ pub fn calculate_control(
     ))
 }
 
+// Tiny or negative targets would turn the frametime math degenerate
+fn clamp_target_fps(target_fps: f64, max_target_fps: f64, min_target_fps: u32) -> f64 {
+    let min_target_fps = f64::from(min_target_fps.max(1));
+    target_fps.clamp(min_target_fps, max_target_fps.max(min_target_fps))
+}
+
 fn get_normalized_last_frame(buffer: &Buffer, target_fps: f64) -> Duration {
     let last_frame = buffer
         .frametime_state
-        .frametimes
+        .raw_frametimes()
         .front()
         .copied()
         .unwrap_or_default();
@@ -112,3 +141,32 @@ fn calculate_control_inner(
 
     error_p as isize
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{clamp_target_fps, get_normalized_last_frame};
+    use crate::framework::scheduler::looper::buffer::Buffer;
+
+    #[test]
+    fn zero_target_fps_is_raised_to_the_floor() {
+        assert!((clamp_target_fps(0.0, 0.0, 10) - 10.0).abs() < f64::EPSILON);
+        // A thermal offset can't push it below the floor either
+        assert!((clamp_target_fps(-5.0, 0.0, 10) - 10.0).abs() < f64::EPSILON);
+        // Even a floor of 0 keeps the target positive
+        assert!((clamp_target_fps(0.0, 0.0, 0) - 1.0).abs() < f64::EPSILON);
+        assert!((clamp_target_fps(57.0, 60.0, 10) - 57.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn zero_target_fps_keeps_the_frame_math_sane() {
+        let frame = Duration::from_millis(20);
+        let buffer = Buffer::with_frametimes(Some(0), &[frame; 60]);
+        let target_fps = clamp_target_fps(0.0, 0.0, 10);
+
+        let normalized = get_normalized_last_frame(&buffer, target_fps);
+
+        assert_eq!(normalized, Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::Write,
+    process,
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+
+use crate::framework::scheduler::capabilities::{Availability, Feature};
+
+const TRACE_MARKER_PATHS: [&str; 2] = [
+    "/sys/kernel/tracing/trace_marker",
+    "/sys/kernel/debug/tracing/trace_marker",
+];
+const MIN_INTERVAL: Duration = Duration::from_millis(10);
+
+// Perfetto counter tracks (C|pid|name|value)
+pub struct TraceMarker {
+    file: Option<File>,
+    pid: u32,
+    last_values: HashMap<String, i64>,
+    last_write: Instant,
+}
+
+impl TraceMarker {
+    pub fn new(enabled: bool) -> Self {
+        let file = if enabled {
+            let file = TRACE_MARKER_PATHS
+                .iter()
+                .find_map(|path| OpenOptions::new().write(true).open(path).ok());
+            if file.is_some() {
+                info!("Writing decisions to trace_marker");
+            } else {
+                warn!("trace_marker is not writable, tracing disabled");
+            }
+            file
+        } else {
+            None
+        };
+
+        Self {
+            file,
+            pid: process::id(),
+            last_values: HashMap::new(),
+            last_write: Instant::now(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        control: isize,
+        is_janked: bool,
+        thermal_fps_offset: f64,
+        cpu_freqs: impl Iterator<Item = (i32, isize)>,
+    ) {
+        if self.file.is_none() || self.last_write.elapsed() < MIN_INTERVAL {
+            return;
+        }
+        self.last_write = Instant::now();
+
+        self.mark("fas_rs_control_khz", control as i64);
+        self.mark("fas_rs_janked", i64::from(is_janked));
+        self.mark("fas_rs_thermal_fps_offset", thermal_fps_offset as i64);
+        for (policy, freq) in cpu_freqs {
+            self.mark(&format!("fas_rs_policy{policy}_freq_khz"), freq as i64);
+        }
+    }
+
+    fn mark(&mut self, name: &str, value: i64) {
+        if self.last_values.get(name) == Some(&value) {
+            return;
+        }
+
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+
+        // Selinux may still deny the write even though open succeeded
+        if let Err(e) = writeln!(file, "C|{}|{name}|{value}", self.pid) {
+            warn!("Failed to write trace_marker, tracing disabled: {e}");
+            self.file = None;
+            return;
+        }
+
+        self.last_values.insert(name.to_string(), value);
+    }
+}
+
+impl Feature for TraceMarker {
+    fn availability(&self) -> Availability {
+        self.file
+            .as_ref()
+            .map(|_| ())
+            .ok_or("off (trace_marker not writable)")
+    }
+}
@@ -21,6 +21,7 @@ use anyhow::Result;
 #[cfg(debug_assertions)]
 use log::debug;
 
+use super::capabilities::{Availability, Feature};
 use crate::{Config, Mode, framework::config::TemperatureThreshold};
 
 pub struct Thermal {
@@ -83,6 +84,16 @@ impl Thermal {
             .filter_map(|path| fs::read_to_string(path).ok())
             .map(|temp| temp.trim().parse::<u64>().unwrap_or_default())
             .max()
-            .unwrap();
+            .unwrap_or_default();
+    }
+}
+
+impl Feature for Thermal {
+    fn availability(&self) -> Availability {
+        if self.nodes.is_empty() {
+            Err("off (no cpu thermal zone)")
+        } else {
+            Ok(())
+        }
     }
 }
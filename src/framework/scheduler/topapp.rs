@@ -18,8 +18,12 @@
 use std::time::{Duration, Instant};
 
 use dumpsys_rs::Dumpsys;
+#[cfg(debug_assertions)]
+use log::debug;
 
 const REFRESH_TIME: Duration = Duration::from_secs(1);
+const STRETCHED_REFRESH_TIME: Duration = Duration::from_secs(3);
+const STABLE_TIME: Duration = Duration::from_mins(5);
 
 #[derive(Default)]
 struct WindowsInfo {
@@ -50,10 +54,46 @@ impl WindowsInfo {
     }
 }
 
+// Polls slower once the top apps stayed the same for STABLE_TIME
+struct Cadence {
+    refresh_time: Duration,
+    stable_since: Instant,
+}
+
+impl Cadence {
+    fn new() -> Self {
+        Self {
+            refresh_time: REFRESH_TIME,
+            stable_since: Instant::now(),
+        }
+    }
+
+    fn observe(&mut self, changed: bool) {
+        if changed {
+            self.restore();
+        } else if self.refresh_time != STRETCHED_REFRESH_TIME
+            && self.stable_since.elapsed() > STABLE_TIME
+        {
+            #[cfg(debug_assertions)]
+            debug!("Top apps stable, stretching refresh time");
+            self.refresh_time = STRETCHED_REFRESH_TIME;
+        }
+    }
+
+    // Returns whether the cadence was stretched
+    fn restore(&mut self) -> bool {
+        let stretched = self.refresh_time != REFRESH_TIME;
+        self.stable_since = Instant::now();
+        self.refresh_time = REFRESH_TIME;
+        stretched
+    }
+}
+
 pub struct TopAppsWatcher {
     windows_dumper: Dumpsys,
     cache: WindowsInfo,
     last_refresh: Instant,
+    cadence: Cadence,
 }
 
 impl TopAppsWatcher {
@@ -69,6 +109,7 @@ impl TopAppsWatcher {
             windows_dumper,
             cache: WindowsInfo::default(),
             last_refresh: Instant::now(),
+            cadence: Cadence::new(),
         }
     }
 
@@ -80,22 +121,91 @@ impl TopAppsWatcher {
         self.cache().visible_freeform_window
     }
 
+    pub const fn refresh_time(&self) -> Duration {
+        self.cadence.refresh_time
+    }
+
+    // The cached view may be stale, refresh right away
+    pub fn restore_cadence(&mut self) {
+        if self.cadence.restore() {
+            self.refresh();
+        }
+    }
+
     fn cache(&mut self) -> &WindowsInfo {
-        if self.last_refresh.elapsed() > REFRESH_TIME {
-            let dump = loop {
-                match self.windows_dumper.dump(&["visible-apps"]) {
-                    Ok(dump) => break dump,
-                    Err(e) => {
-                        log::error!("Failed to dump windows: {}, retrying", e);
-                        std::thread::sleep(Duration::from_secs(1));
-                    }
+        if self.last_refresh.elapsed() > self.cadence.refresh_time {
+            self.refresh();
+        }
+
+        &self.cache
+    }
+
+    fn refresh(&mut self) {
+        let dump = loop {
+            match self.windows_dumper.dump(&["visible-apps"]) {
+                Ok(dump) => break dump,
+                Err(e) => {
+                    log::error!("Failed to dump windows: {}, retrying", e);
+                    std::thread::sleep(Duration::from_secs(1));
                 }
-            };
-            self.cache = WindowsInfo::new(&dump);
+            }
+        };
+        let info = WindowsInfo::new(&dump);
+
+        self.cadence.observe(info.pids != self.cache.pids);
+        self.cache = info;
+
+        self.last_refresh = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Cadence, REFRESH_TIME, STABLE_TIME, STRETCHED_REFRESH_TIME};
 
-            self.last_refresh = Instant::now();
+    fn stable_cadence() -> Cadence {
+        Cadence {
+            refresh_time: REFRESH_TIME,
+            stable_since: Instant::now()
+                .checked_sub(STABLE_TIME + Duration::from_secs(1))
+                .unwrap(),
         }
+    }
 
-        &self.cache
+    #[test]
+    fn cadence_stretches_only_after_stable_time() {
+        let mut cadence = Cadence::new();
+        cadence.observe(false);
+        assert_eq!(cadence.refresh_time, REFRESH_TIME);
+
+        let mut cadence = stable_cadence();
+        cadence.observe(false);
+        assert_eq!(cadence.refresh_time, STRETCHED_REFRESH_TIME);
+    }
+
+    #[test]
+    fn top_app_change_restores_cadence() {
+        let mut cadence = stable_cadence();
+        cadence.observe(false);
+        cadence.observe(true);
+
+        assert_eq!(cadence.refresh_time, REFRESH_TIME);
+        assert!(cadence.stable_since.elapsed() < STABLE_TIME);
+        cadence.observe(false);
+        assert_eq!(cadence.refresh_time, REFRESH_TIME);
+    }
+
+    #[test]
+    fn restore_reports_whether_it_was_stretched() {
+        let mut cadence = stable_cadence();
+        assert!(!cadence.restore());
+
+        let mut cadence = stable_cadence();
+        cadence.observe(false);
+        assert!(cadence.restore());
+        assert_eq!(cadence.refresh_time, REFRESH_TIME);
+        assert!(!cadence.restore());
     }
 }
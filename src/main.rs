@@ -29,6 +29,8 @@ mod cpu_common;
 mod file_handler;
 mod framework;
 mod misc;
+#[cfg(test)]
+mod test_utils;
 
 use std::{
     env, fs,
@@ -95,8 +97,8 @@ fn run<S: AsRef<str>>(std_path: S) -> Result<()> {
     let self_pid = process::id();
     let _ = fs::write("/dev/cpuset/background/cgroup.procs", self_pid.to_string());
 
-    let config = Config::new(USER_CONFIG, std_path)?;
-    let cpu = Controller::new()?;
+    let mut config = Config::new(USER_CONFIG, std_path)?;
+    let cpu = Controller::new(&mut config)?;
 
     #[cfg(debug_assertions)]
     debug!("{cpu:#?}");
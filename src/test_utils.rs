@@ -0,0 +1,38 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+// Fresh scratch directory standing in for sysfs or the node directory
+pub fn fixture_dir(name: &str) -> PathBuf {
+    let path = env::temp_dir()
+        .join(format!("fas-rs-{}", process::id()))
+        .join(name);
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path
+}
+
+pub fn write_files(dir: &Path, files: &[(&str, &str)]) {
+    for (file, content) in files {
+        fs::write(dir.join(file), content).unwrap();
+    }
+}